@@ -1,3 +1,5 @@
 fn main() {
+    // Postgres migrations are embedded with sqlx::migrate!
+    println!("cargo:rerun-if-changed=migrations");
     tauri_build::build()
 }
@@ -0,0 +1,123 @@
+-- migrations/001_initial_schema.sql
+-- Cloud schema as it stood before migrations were versioned. Databases
+-- created by older releases already have most of it, so every statement
+-- is idempotent.
+
+DO $$
+BEGIN
+    CREATE TYPE purge_cadence AS ENUM (
+        'never',
+        'every_24_hours',
+        'every_3_days',
+        'every_week',
+        'every_month'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS users (
+    id BIGSERIAL PRIMARY KEY,
+    organization_id VARCHAR(255) NOT NULL,
+    firebase_uid TEXT UNIQUE NOT NULL,
+    email TEXT NOT NULL,
+    display_name TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    purge_cadence purge_cadence NOT NULL DEFAULT 'every_24_hours',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    retain_tags BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE TABLE IF NOT EXISTS clipboard_entries (
+    id BIGSERIAL PRIMARY KEY,
+    organization_id VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    content_type VARCHAR(50) NOT NULL DEFAULT 'text',
+    content_hash VARCHAR(64) NOT NULL,
+    source_app VARCHAR(255) NOT NULL,
+    source_window VARCHAR(255) NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    tags TEXT,
+    is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    image_width BIGINT,
+    image_height BIGINT,
+    byte_size BIGINT,
+    copy_count BIGINT NOT NULL DEFAULT 1,
+    last_source_app VARCHAR(255),
+    content_html TEXT,
+    content_meta TEXT,
+    blob_ref VARCHAR(64)
+);
+
+-- Columns added to clipboard_entries after the first release
+ALTER TABLE clipboard_entries
+    ADD COLUMN IF NOT EXISTS image_width BIGINT,
+    ADD COLUMN IF NOT EXISTS image_height BIGINT,
+    ADD COLUMN IF NOT EXISTS byte_size BIGINT,
+    ADD COLUMN IF NOT EXISTS copy_count BIGINT NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS last_source_app VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS content_html TEXT,
+    ADD COLUMN IF NOT EXISTS content_meta TEXT,
+    ADD COLUMN IF NOT EXISTS blob_ref VARCHAR(64);
+
+-- payment_status is VARCHAR(20) to match the NestJS backend schema, not an enum
+CREATE TABLE IF NOT EXISTS payments (
+    id BIGSERIAL PRIMARY KEY,
+    stripe_session_id VARCHAR(255) UNIQUE NOT NULL,
+    stripe_payment_intent_id VARCHAR(255),
+    organization_id VARCHAR(255) NOT NULL,
+    firebase_uid VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    amount_paid INTEGER NOT NULL DEFAULT 0,
+    currency VARCHAR(10) NOT NULL DEFAULT 'usd',
+    payment_status VARCHAR(20) NOT NULL DEFAULT 'unpaid',
+    plan_type VARCHAR(50) NOT NULL DEFAULT 'lifetime',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    paid_at TIMESTAMPTZ,
+    metadata JSONB
+);
+
+CREATE TABLE IF NOT EXISTS capture_settings (
+    organization_id VARCHAR(255) PRIMARY KEY,
+    settings TEXT NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS content_blobs (
+    organization_id VARCHAR(255) NOT NULL,
+    hash VARCHAR(64) NOT NULL,
+    data BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, hash)
+);
+
+CREATE TABLE IF NOT EXISTS capture_rules (
+    id BIGSERIAL PRIMARY KEY,
+    organization_id VARCHAR(255) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    position BIGINT NOT NULL DEFAULT 0,
+    conditions TEXT NOT NULL DEFAULT '{}',
+    actions TEXT NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_users_firebase_uid ON users(firebase_uid);
+CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
+CREATE INDEX IF NOT EXISTS idx_users_organization_id ON users(organization_id);
+
+CREATE INDEX IF NOT EXISTS idx_clipboard_content_hash ON clipboard_entries(content_hash);
+CREATE INDEX IF NOT EXISTS idx_clipboard_created_at ON clipboard_entries(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_clipboard_source_app ON clipboard_entries(source_app);
+CREATE INDEX IF NOT EXISTS idx_clipboard_organization_id ON clipboard_entries(organization_id);
+
+CREATE INDEX IF NOT EXISTS idx_capture_rules_organization_id ON capture_rules(organization_id);
+
+CREATE INDEX IF NOT EXISTS idx_payments_firebase_uid ON payments(firebase_uid);
+CREATE INDEX IF NOT EXISTS idx_payments_organization_id ON payments(organization_id);
+CREATE INDEX IF NOT EXISTS idx_payments_stripe_session_id ON payments(stripe_session_id);
+CREATE INDEX IF NOT EXISTS idx_payments_status ON payments(payment_status);
@@ -0,0 +1,17 @@
+-- migrations/002_per_organization_dedupe.sql
+-- Dedupe used to be global; it is per organization now. Drop the old
+-- column constraint and move legacy md5 hashes of text rows to SHA-256
+-- (images keep theirs, the pixels aren't stored here).
+
+ALTER TABLE clipboard_entries DROP CONSTRAINT IF EXISTS clipboard_entries_content_hash_key;
+
+UPDATE clipboard_entries
+SET content_hash = encode(sha256(convert_to(content, 'UTF8')), 'hex')
+WHERE length(content_hash) = 32 AND content_type <> 'image'
+  AND NOT EXISTS (
+      SELECT 1 FROM clipboard_entries dup
+      WHERE dup.organization_id = clipboard_entries.organization_id
+        AND dup.content_hash = encode(sha256(convert_to(clipboard_entries.content, 'UTF8')), 'hex')
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_clipboard_org_content_hash ON clipboard_entries(organization_id, content_hash);
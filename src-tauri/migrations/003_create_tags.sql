@@ -1,33 +1,56 @@
 -- migrations/003_create_tags.sql
 CREATE TABLE IF NOT EXISTS tags (
     id BIGSERIAL PRIMARY KEY,
-    organization_id TEXT NOT NULL,
-    name TEXT NOT NULL,
-    color TEXT NOT NULL,
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    updated_at TIMESTAMPTZ DEFAULT NOW()
+    organization_id VARCHAR(255) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    color VARCHAR(7) NOT NULL DEFAULT '#6B7280',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
--- Drop existing indexes if they exist and recreate with IF NOT EXISTS
-DROP INDEX IF EXISTS idx_tags_organization_id;
+-- Earlier drafts of this file created the same unique index under another name
 DROP INDEX IF EXISTS idx_tags_organization_name;
 
+-- Servers that never got the unique index below may hold tags that differ
+-- only in case. Merge each group into its oldest tag, moving entry links
+-- over, so the index can be created.
+CREATE TEMP TABLE duplicate_tags ON COMMIT DROP AS
+SELECT id, first_value(id) OVER (
+    PARTITION BY organization_id, LOWER(name) ORDER BY created_at, id
+) AS keep_id
+FROM tags;
+
+DELETE FROM duplicate_tags WHERE id = keep_id;
+
+DO $$
+BEGIN
+    IF to_regclass('entry_tags') IS NOT NULL THEN
+        INSERT INTO entry_tags (entry_id, tag_id)
+        SELECT et.entry_id, d.keep_id
+        FROM entry_tags et
+        JOIN duplicate_tags d ON d.id = et.tag_id
+        ON CONFLICT DO NOTHING;
+    END IF;
+END $$;
+
+-- Links to the merged tags go with them (ON DELETE CASCADE)
+DELETE FROM tags WHERE id IN (SELECT id FROM duplicate_tags);
+
 CREATE INDEX IF NOT EXISTS idx_tags_organization_id ON tags(organization_id);
-CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_organization_name ON tags(organization_id, LOWER(name));
+CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name);
+-- Tag names are unique per organization regardless of case
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_organization_name_unique ON tags(organization_id, LOWER(name));
 
--- Create the function if it doesn't exist
 CREATE OR REPLACE FUNCTION update_updated_at_column()
 RETURNS TRIGGER AS $$
 BEGIN
     NEW.updated_at = NOW();
     RETURN NEW;
 END;
-$$ language 'plpgsql';
+$$ LANGUAGE plpgsql;
 
--- Drop existing trigger if it exists and recreate
 DROP TRIGGER IF EXISTS update_tags_updated_at ON tags;
-
-CREATE TRIGGER update_tags_updated_at 
-    BEFORE UPDATE ON tags 
-    FOR EACH ROW 
-    EXECUTE FUNCTION update_updated_at_column();
\ No newline at end of file
+CREATE TRIGGER update_tags_updated_at
+    BEFORE UPDATE ON tags
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,22 @@
+-- migrations/004_updated_at_triggers.sql
+-- Keep updated_at current on the tables the backend writes too, not just
+-- tags. capture_rules is left out: clients push the time of the edit on
+-- the device, which a trigger would overwrite.
+
+DROP TRIGGER IF EXISTS update_users_updated_at ON users;
+CREATE TRIGGER update_users_updated_at
+    BEFORE UPDATE ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_payments_updated_at ON payments;
+CREATE TRIGGER update_payments_updated_at
+    BEFORE UPDATE ON payments
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_capture_settings_updated_at ON capture_settings;
+CREATE TRIGGER update_capture_settings_updated_at
+    BEFORE UPDATE ON capture_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
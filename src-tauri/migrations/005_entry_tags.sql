@@ -0,0 +1,105 @@
+-- migrations/005_entry_tags.sql
+-- Entry tags move from the JSON text in clipboard_entries.tags to an
+-- entry_tags join table. The column stays as a cache of the linked names,
+-- refreshed by triggers when a link changes or a tag is renamed; deleting a
+-- tag cascades to its links and so to the cache as well.
+
+CREATE TABLE IF NOT EXISTS entry_tags (
+    entry_id BIGINT NOT NULL REFERENCES clipboard_entries(id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (entry_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_entry_tags_tag_id ON entry_tags(tag_id);
+
+-- Same leniency as json_to_tags on the client: a JSON array, a bracketed
+-- list that isn't valid JSON, or a bare name
+CREATE OR REPLACE FUNCTION pg_temp.legacy_tag_names(tags TEXT)
+RETURNS SETOF TEXT AS $$
+DECLARE
+    cleaned TEXT := btrim(replace(replace(tags, '\"', '"'), '\\', '\'));
+BEGIN
+    IF cleaned IS NULL OR cleaned = '' THEN
+        RETURN;
+    END IF;
+
+    BEGIN
+        RETURN QUERY SELECT btrim(value) FROM json_array_elements_text(cleaned::json) AS value;
+        RETURN;
+    EXCEPTION WHEN others THEN
+        NULL;
+    END;
+
+    IF left(cleaned, 1) = '[' AND right(cleaned, 1) = ']' THEN
+        RETURN QUERY
+            SELECT btrim(btrim(part), '"')
+            FROM unnest(string_to_array(substr(cleaned, 2, length(cleaned) - 2), ',')) AS part;
+    ELSE
+        RETURN NEXT cleaned;
+    END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TEMP TABLE legacy_entry_tags ON COMMIT DROP AS
+SELECT DISTINCT e.id AS entry_id, e.organization_id, name
+FROM clipboard_entries e, pg_temp.legacy_tag_names(e.tags) AS name
+WHERE e.tags IS NOT NULL AND name <> '' AND length(name) <= 100;
+
+INSERT INTO tags (organization_id, name)
+SELECT DISTINCT ON (organization_id, LOWER(name)) organization_id, name
+FROM legacy_entry_tags
+ORDER BY organization_id, LOWER(name), name
+ON CONFLICT DO NOTHING;
+
+INSERT INTO entry_tags (entry_id, tag_id)
+SELECT l.entry_id, t.id
+FROM legacy_entry_tags l
+JOIN tags t ON t.organization_id = l.organization_id AND LOWER(t.name) = LOWER(l.name)
+ON CONFLICT DO NOTHING;
+
+-- JSON array of an entry's tag names in the order they were added, NULL
+-- when it has none
+CREATE OR REPLACE FUNCTION entry_tag_names(entry BIGINT)
+RETURNS TEXT AS $$
+    SELECT json_agg(t.name ORDER BY et.created_at, t.name)::text
+    FROM entry_tags et
+    JOIN tags t ON t.id = et.tag_id
+    WHERE et.entry_id = entry;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION refresh_entry_tags_cache()
+RETURNS TRIGGER AS $$
+DECLARE
+    entry BIGINT := CASE WHEN TG_OP = 'DELETE' THEN OLD.entry_id ELSE NEW.entry_id END;
+BEGIN
+    UPDATE clipboard_entries SET tags = entry_tag_names(id) WHERE id = entry;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION refresh_renamed_tag_cache()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE clipboard_entries SET tags = entry_tag_names(id)
+    WHERE id IN (SELECT entry_id FROM entry_tags WHERE tag_id = NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS entry_tags_refresh_cache ON entry_tags;
+CREATE TRIGGER entry_tags_refresh_cache
+    AFTER INSERT OR DELETE ON entry_tags
+    FOR EACH ROW
+    EXECUTE FUNCTION refresh_entry_tags_cache();
+
+DROP TRIGGER IF EXISTS tags_refresh_entry_cache ON tags;
+CREATE TRIGGER tags_refresh_entry_cache
+    AFTER UPDATE OF name ON tags
+    FOR EACH ROW
+    WHEN (OLD.name IS DISTINCT FROM NEW.name)
+    EXECUTE FUNCTION refresh_renamed_tag_cache();
+
+-- Rewrite every cache in the canonical form, including unparseable ones
+UPDATE clipboard_entries SET tags = entry_tag_names(id)
+WHERE tags IS DISTINCT FROM entry_tag_names(id);
@@ -0,0 +1,537 @@
+// src/capture/classifier.rs
+//
+// Decides what kind of text a copy is. Each detector looks at the trimmed
+// text and either declines or returns metadata for its type; the first match
+// in `DETECTORS` becomes the entry's `content_type`, and every match is listed
+// in the metadata so the UI and search can filter on secondary types too.
+// New kinds are added by writing a detector and slotting it into the list.
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+/// Detector-specific fields, stored as the entry's `content_meta` JSON.
+pub type Metadata = Map<String, Value>;
+
+type Detector = fn(&str) -> Option<Metadata>;
+
+/// Longer copies are stored as plain "text" without running the detectors.
+const MAX_CLASSIFY_BYTES: usize = 256 * 1024;
+
+/// Ordered from most to least specific; the first match is the primary type.
+const DETECTORS: &[(&str, Detector)] = &[
+    ("url", detect_url),
+    ("email", detect_email),
+    ("uuid", detect_uuid),
+    ("color", detect_color),
+    ("ip_address", detect_ip_address),
+    ("date", detect_date),
+    ("phone", detect_phone),
+    ("numeric", detect_numeric),
+    ("json", detect_json),
+    ("xml", detect_xml),
+    ("file_path", detect_file_path),
+    ("shell", detect_shell),
+    ("markdown", detect_markdown),
+    ("code", detect_code),
+    ("yaml", detect_yaml),
+];
+
+/// Every `content_type` an entry can have: the detectors' types, plain
+/// text, and the non-text captures.
+pub fn known_types() -> impl Iterator<Item = &'static str> {
+    ["text", "image", "files"]
+        .into_iter()
+        .chain(DETECTORS.iter().map(|(name, _)| *name))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub content_type: String,
+    pub metadata: Metadata,
+}
+
+impl Classification {
+    pub fn metadata_json(&self) -> String {
+        Value::Object(self.metadata.clone()).to_string()
+    }
+}
+
+pub fn classify(text: &str) -> Classification {
+    let trimmed = text.trim();
+    if trimmed.is_empty() || trimmed.len() > MAX_CLASSIFY_BYTES {
+        return Classification {
+            content_type: "text".to_string(),
+            metadata: Metadata::new(),
+        };
+    }
+
+    let mut content_type = None;
+    let mut metadata = Metadata::new();
+    let mut types = Vec::new();
+
+    for (name, detector) in DETECTORS {
+        let Some(found) = detector(trimmed) else {
+            continue;
+        };
+
+        types.push(Value::from(*name));
+        if content_type.is_none() {
+            content_type = Some(name.to_string());
+            metadata = found;
+        }
+    }
+
+    metadata.insert("types".to_string(), Value::Array(types));
+    metadata.insert("lines".to_string(), json!(trimmed.lines().count()));
+
+    Classification {
+        content_type: content_type.unwrap_or_else(|| "text".to_string()),
+        metadata,
+    }
+}
+
+fn meta(value: Value) -> Option<Metadata> {
+    match value {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+fn is_single_line(text: &str) -> bool {
+    !text.contains('\n')
+}
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[A-Za-z0-9._%+-]+@([A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,})$")
+        .expect("valid email pattern")
+});
+static UUID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?i)\{?[0-9a-f]{8}-[0-9a-f]{4}-([0-9a-f])[0-9a-f]{3}-[0-9a-f]{4}-[0-9a-f]{12}\}?$")
+        .expect("valid uuid pattern")
+});
+static HEX_COLOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^#(?i)([0-9a-f]{3}|[0-9a-f]{4}|[0-9a-f]{6}|[0-9a-f]{8})$").expect("valid hex color pattern")
+});
+static FUNC_COLOR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?i)(rgba?|hsla?)\(\s*(\d{1,3})%?\s*[, ]\s*(\d{1,3})%?\s*[, ]\s*(\d{1,3})%?\s*(?:[,/]\s*[\d.]+%?\s*)?\)$")
+        .expect("valid color function pattern")
+});
+static PHONE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\+?\(?\d[\d\s().-]{5,}\d$").expect("valid phone pattern"));
+static XML_ROOT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:<\?xml[^>]*\?>\s*)?(?:<!--.*?-->\s*)*(?:<!DOCTYPE[^>]*>\s*)?<([A-Za-z_][\w:.-]*)")
+        .expect("valid xml pattern")
+});
+static MARKDOWN_FEATURES: Lazy<Vec<(&str, Regex)>> = Lazy::new(|| {
+    [
+        ("heading", r"(?m)^#{1,6} \S"),
+        ("list", r"(?m)^\s*(?:[-*+]|\d+\.) \S"),
+        ("link", r"\[[^\]]+\]\([^)\s]+\)"),
+        ("code_fence", r"(?m)^```"),
+        ("emphasis", r"\*\*[^*\n]+\*\*|__[^_\n]+__"),
+        ("table", r"(?m)^\|.*\|\s*$\n^\|[\s:|-]+\|\s*$"),
+    ]
+    .into_iter()
+    .map(|(name, pattern)| (name, Regex::new(pattern).expect("valid markdown pattern")))
+    .collect()
+});
+static YAML_KEY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:- )?[\w.-]+:(?:\s|$)").expect("valid yaml pattern"));
+
+fn detect_url(text: &str) -> Option<Metadata> {
+    if !is_single_line(text) || text.contains(' ') {
+        return None;
+    }
+
+    let url = url::Url::parse(text).ok()?;
+    if !matches!(url.scheme(), "http" | "https" | "ftp" | "file" | "mailto") {
+        return None;
+    }
+
+    meta(json!({ "scheme": url.scheme(), "host": url.host_str() }))
+}
+
+fn detect_email(text: &str) -> Option<Metadata> {
+    let captures = EMAIL.captures(text)?;
+    meta(json!({ "domain": captures[1].to_lowercase() }))
+}
+
+fn detect_uuid(text: &str) -> Option<Metadata> {
+    let captures = UUID.captures(text)?;
+    meta(json!({ "version": captures[1].to_string() }))
+}
+
+fn detect_color(text: &str) -> Option<Metadata> {
+    if let Some(captures) = HEX_COLOR.captures(text) {
+        let hex = captures[1].to_lowercase();
+        // #rgb(a) is shorthand for #rrggbb(aa)
+        let full: String = if hex.len() <= 4 {
+            hex.chars().flat_map(|c| [c, c]).collect()
+        } else {
+            hex
+        };
+        let channel = |i: usize| u8::from_str_radix(&full[i..i + 2], 16).ok();
+
+        return meta(json!({
+            "format": "hex",
+            "hex": format!("#{}", &full[..6]),
+            "rgb": [channel(0)?, channel(2)?, channel(4)?],
+        }));
+    }
+
+    let captures = FUNC_COLOR.captures(text)?;
+    let format = captures[1].to_lowercase();
+    let values: Vec<u16> = (2..=4).filter_map(|i| captures[i].parse().ok()).collect();
+
+    if format.starts_with("rgb") {
+        if values.iter().any(|v| *v > 255) {
+            return None;
+        }
+        return meta(json!({
+            "format": format,
+            "hex": format!("#{:02x}{:02x}{:02x}", values[0], values[1], values[2]),
+            "rgb": values,
+        }));
+    }
+
+    meta(json!({ "format": format, "hsl": values }))
+}
+
+fn detect_ip_address(text: &str) -> Option<Metadata> {
+    let (ip, port) = match text.parse::<IpAddr>() {
+        Ok(ip) => (ip, None),
+        Err(_) => {
+            let addr = text.parse::<SocketAddr>().ok()?;
+            (addr.ip(), Some(addr.port()))
+        }
+    };
+
+    meta(json!({
+        "version": if ip.is_ipv4() { 4 } else { 6 },
+        "port": port,
+        "private": match ip {
+            IpAddr::V4(v4) => v4.is_private() || v4.is_loopback(),
+            IpAddr::V6(v6) => v6.is_loopback(),
+        },
+    }))
+}
+
+fn detect_date(text: &str) -> Option<Metadata> {
+    if !is_single_line(text) || text.len() > 40 {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return meta(json!({ "format": "rfc3339", "iso": dt.to_rfc3339() }));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(text) {
+        return meta(json!({ "format": "rfc2822", "iso": dt.to_rfc3339() }));
+    }
+
+    const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y", "%d/%m/%Y", "%m/%d/%Y", "%B %d, %Y", "%d %B %Y"];
+    DATE_FORMATS.iter().find_map(|format| {
+        let date = NaiveDate::parse_from_str(text, format).ok()?;
+        meta(json!({ "format": format, "iso": date.to_string() }))
+    })
+}
+
+fn detect_phone(text: &str) -> Option<Metadata> {
+    if !PHONE.is_match(text) {
+        return None;
+    }
+
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    // Plain digit runs are numbers unless written as +<country code>...
+    let has_separators = text.chars().any(|c| " ().-".contains(c));
+    if !(7..=15).contains(&digits.len()) || !(text.starts_with('+') || has_separators) {
+        return None;
+    }
+
+    meta(json!({
+        "digits": digits,
+        "international": text.starts_with('+'),
+    }))
+}
+
+fn detect_numeric(text: &str) -> Option<Metadata> {
+    if !text.chars().all(|c| c.is_ascii_digit() || c.is_whitespace() || ".,-+".contains(c))
+        || !text.chars().any(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    // Versions, IPs and dates use the same characters but aren't one number
+    let value = text.replace([',', ' '], "").parse::<f64>().ok()?;
+    meta(json!({ "value": value }))
+}
+
+fn detect_json(text: &str) -> Option<Metadata> {
+    if !(text.starts_with('{') || text.starts_with('[')) {
+        return None;
+    }
+
+    match serde_json::from_str::<Value>(text).ok()? {
+        Value::Object(map) => meta(json!({ "json_kind": "object", "keys": map.len() })),
+        Value::Array(items) => meta(json!({ "json_kind": "array", "items": items.len() })),
+        _ => None,
+    }
+}
+
+fn detect_xml(text: &str) -> Option<Metadata> {
+    if !text.starts_with('<') || !text.ends_with('>') {
+        return None;
+    }
+
+    let root = XML_ROOT.captures(text)?[1].to_string();
+    // The root must be closed (or self-closing) for this to be a document
+    let closed = text.ends_with(&format!("</{}>", root)) || text.ends_with("/>");
+    if !closed {
+        return None;
+    }
+
+    let is_html = root.eq_ignore_ascii_case("html") || text.contains("<!DOCTYPE html");
+    meta(json!({ "root": root, "html": is_html }))
+}
+
+/// Only looks at the text: classification runs on every copy and over whole
+/// histories in migrations, so it never touches the filesystem.
+fn detect_file_path(text: &str) -> Option<Metadata> {
+    if !is_single_line(text) || text.len() > 4096 {
+        return None;
+    }
+
+    let looks_like_path = text.starts_with('/')
+        || text.starts_with("~/")
+        || text.starts_with("./")
+        || text.starts_with("../")
+        || text.starts_with("\\\\")
+        || (text.len() > 2 && text.as_bytes()[1] == b':' && matches!(text.as_bytes()[2], b'\\' | b'/'));
+    if !looks_like_path || text.contains("://") {
+        return None;
+    }
+
+    let extension = Path::new(text).extension().map(|e| e.to_string_lossy().to_lowercase());
+    meta(json!({ "extension": extension }))
+}
+
+const SHELL_PROGRAMS: &[&str] = &[
+    "apt", "brew", "cargo", "cat", "cd", "chmod", "chown", "cp", "curl", "docker", "echo",
+    "export", "find", "git", "grep", "kubectl", "ls", "make", "mkdir", "mv", "npm", "npx",
+    "pip", "pnpm", "python", "python3", "rm", "scp", "sed", "ssh", "sudo", "systemctl",
+    "tar", "wget", "yarn",
+];
+
+fn detect_shell(text: &str) -> Option<Metadata> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() || lines.len() > 20 {
+        return None;
+    }
+
+    let first = lines[0].trim();
+    let prompted = first.starts_with("$ ");
+    let command = first.trim_start_matches('$').trim_start();
+    let program = command.split_whitespace().next()?;
+
+    // Every line should be a command (or a continuation of one)
+    let all_commands = lines.iter().all(|line| {
+        let line = line.trim().trim_start_matches('$').trim_start();
+        line.split_whitespace()
+            .next()
+            .is_some_and(|word| SHELL_PROGRAMS.contains(&word) || word.starts_with("./"))
+            || line.starts_with("&&")
+            || line.starts_with('|')
+    });
+
+    // Without a prompt, a lone program name ("make", "find") is just a word
+    let has_arguments = command.split_whitespace().nth(1).is_some() || lines.len() > 1;
+    if !((prompted && !command.is_empty()) || (all_commands && has_arguments && SHELL_PROGRAMS.contains(&program))) {
+        return None;
+    }
+
+    meta(json!({ "program": program, "commands": lines.len() }))
+}
+
+fn detect_markdown(text: &str) -> Option<Metadata> {
+    let found: Vec<&str> = MARKDOWN_FEATURES
+        .iter()
+        .filter(|(_, regex)| regex.is_match(text))
+        .map(|(name, _)| *name)
+        .collect();
+
+    // One feature alone is too weak (a "# comment", a bullet)
+    if found.len() < 2 {
+        return None;
+    }
+
+    meta(json!({ "features": found }))
+}
+
+/// (language, signals). A language needs two distinct signals to count.
+const LANGUAGE_SIGNALS: &[(&str, &[&str])] = &[
+    ("rust", &["fn ", "let mut ", "impl ", "pub fn", "::", "-> ", "match ", "use std", "#[derive", "&self", "Some(", "Ok("]),
+    ("python", &["def ", "import ", "from ", "self.", "elif ", "print(", "__init__", "None", "    return", "lambda "]),
+    ("javascript", &["function ", "const ", "=> ", "console.log", "let ", "require(", "export ", "async ", "await ", "===", "document."]),
+    ("typescript", &["interface ", ": string", ": number", "export type", "implements ", "as const", "<T>"]),
+    ("go", &["func ", "package ", ":= ", "fmt.", "err != nil", "go func", "chan "]),
+    ("java", &["public class", "public static void", "System.out", "private ", "import java", "@Override", "new "]),
+    ("csharp", &["using System", "namespace ", "public class", "Console.Write", "var ", "async Task", "get; set;"]),
+    ("c", &["#include", "int main(", "printf(", "malloc(", "->", "sizeof(", "void "]),
+    ("cpp", &["#include", "std::", "cout <<", "template<", "nullptr", "namespace "]),
+    ("sql", &["SELECT ", "FROM ", "WHERE ", "INSERT INTO", "UPDATE ", "JOIN ", "CREATE TABLE", "GROUP BY"]),
+    ("css", &["{", "}", "color:", "margin:", "padding:", "display:", "font-", "px;"]),
+    ("html", &["<div", "<span", "</", "class=\"", "<a ", "<p>", "href="]),
+];
+
+fn detect_code(text: &str) -> Option<Metadata> {
+    let (language, score) = LANGUAGE_SIGNALS
+        .iter()
+        .map(|(language, signals)| {
+            let score = signals.iter().filter(|signal| text.contains(*signal)).count();
+            (*language, score)
+        })
+        .max_by_key(|(_, score)| *score)?;
+
+    // Code also tends to use brackets, semicolons or indentation
+    let structured = text.contains(['{', '(', ';']) || text.lines().any(|l| l.starts_with("    "));
+    if score < 2 || !structured {
+        return None;
+    }
+
+    meta(json!({ "language": language, "confidence": score }))
+}
+
+fn detect_yaml(text: &str) -> Option<Metadata> {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    if lines.len() < 2 || text.contains(['{', ';']) {
+        return None;
+    }
+
+    let keyed = lines.iter().filter(|l| YAML_KEY.is_match(l)).count();
+    let listed = lines.iter().filter(|l| l.trim_start().starts_with("- ")).count();
+    if keyed < 2 || keyed + listed < lines.len() * 3 / 4 {
+        return None;
+    }
+
+    meta(json!({ "keys": keyed, "document_start": text.starts_with("---") }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn primary(text: &str) -> String {
+        classify(text).content_type
+    }
+
+    fn types(text: &str) -> Vec<String> {
+        classify(text).metadata["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn plain_text_stays_text() {
+        assert_eq!(primary("Remember to buy milk"), "text");
+        assert_eq!(primary("   "), "text");
+        assert!(types("hello there").is_empty());
+    }
+
+    #[test]
+    fn urls_and_emails() {
+        assert_eq!(primary("https://example.com/path?q=1"), "url");
+        assert_eq!(classify("https://example.com/").metadata["host"], "example.com");
+        assert_eq!(primary("see https://example.com"), "text");
+        assert_eq!(primary("Jane.Doe@Example.ORG"), "email");
+        assert_eq!(classify("Jane.Doe@Example.ORG").metadata["domain"], "example.org");
+        assert_eq!(primary("jane@localhost"), "text");
+    }
+
+    #[test]
+    fn uuids_colors_and_ips() {
+        assert_eq!(primary("123e4567-e89b-42d3-a456-426614174000"), "uuid");
+        assert_eq!(classify("123e4567-e89b-42d3-a456-426614174000").metadata["version"], "4");
+
+        assert_eq!(primary("#fa0"), "color");
+        assert_eq!(classify("#fa0").metadata["hex"], "#ffaa00");
+        assert_eq!(primary("rgb(255, 128, 0)"), "color");
+        assert_eq!(primary("rgb(300, 0, 0)"), "text");
+
+        assert_eq!(primary("192.168.1.10"), "ip_address");
+        assert_eq!(classify("192.168.1.10:8080").metadata["port"], 8080);
+        assert_eq!(primary("999.1.1.1"), "text");
+    }
+
+    #[test]
+    fn dates_phones_and_numbers() {
+        assert_eq!(primary("2026-09-01"), "date");
+        assert_eq!(primary("2026-09-01T10:00:00Z"), "date");
+        assert_eq!(primary("+1 (555) 123-4567"), "phone");
+        assert_eq!(primary("1,234.56"), "numeric");
+        assert_eq!(primary("5551234567"), "numeric");
+        assert_eq!(primary("v1.2.3"), "text");
+    }
+
+    #[test]
+    fn structured_documents() {
+        assert_eq!(primary(r#"{"a": 1, "b": [2]}"#), "json");
+        assert_eq!(classify(r#"{"a": 1, "b": [2]}"#).metadata["keys"], 2);
+        assert_eq!(primary("{not json"), "text");
+
+        assert_eq!(primary("<note><to>Tove</to></note>"), "xml");
+        assert_eq!(primary("<b>bold</i>"), "text");
+
+        assert_eq!(primary("name: demo\nversion: 2\nitems:\n  - one"), "yaml");
+        assert_eq!(primary("# Title\n\n- one\n- **two**"), "markdown");
+    }
+
+    #[test]
+    fn file_paths_are_classified_without_touching_the_disk() {
+        let classification = classify("/definitely/not/here/report.PDF");
+        assert_eq!(classification.content_type, "file_path");
+        assert_eq!(classification.metadata["extension"], "pdf");
+        assert!(!classification.metadata.contains_key("exists"));
+
+        assert_eq!(primary(r"C:\Users\me\notes.txt"), "file_path");
+        assert_eq!(primary("file:///tmp/a.txt"), "url");
+        assert_eq!(primary("/usr/bin and more\n/second line"), "text");
+    }
+
+    #[test]
+    fn shell_commands_need_more_than_a_program_name() {
+        assert_eq!(primary("git status"), "shell");
+        assert_eq!(primary("$ ls"), "shell");
+        assert_eq!(primary("cd app\nnpm install\n| tee log"), "shell");
+        assert_eq!(classify("cargo build --release").metadata["program"], "cargo");
+
+        assert_eq!(primary("make"), "text");
+        assert_eq!(primary("find"), "text");
+        assert_eq!(primary("Find me later"), "text");
+    }
+
+    #[test]
+    fn code_needs_two_signals_and_structure() {
+        let rust = "pub fn main() {\n    let mut x = Some(1);\n}";
+        assert_eq!(primary(rust), "code");
+        assert_eq!(classify(rust).metadata["language"], "rust");
+        assert_eq!(primary("let me know"), "text");
+    }
+
+    #[test]
+    fn secondary_types_are_listed() {
+        // A path-like URL is still a URL first
+        let all = types("file:///etc/hosts");
+        assert_eq!(all.first().map(String::as_str), Some("url"));
+        assert!(known_types().any(|t| t == "files"));
+        assert!(DETECTORS.iter().all(|(name, _)| known_types().any(|t| t == *name)));
+    }
+}
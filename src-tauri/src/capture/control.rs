@@ -0,0 +1,115 @@
+// src/capture/control.rs
+//
+// Pause / resume / incognito. The single source of truth is
+// `AppState.is_clipboard_monitoring` (plus the incognito deadline); every
+// change goes through `apply` so the tray menu and the `clipboard-status`
+// event never disagree with it.
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{async_runtime, AppHandle, Emitter, Manager};
+
+use crate::{AppState, TrayCaptureMenu};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureStatus {
+    pub status: String, // "ready", "paused" or "incognito"
+    pub message: String,
+    pub resume_at: Option<u64>, // Unix seconds, only for incognito
+}
+
+pub fn is_capture_enabled(app_handle: &AppHandle) -> bool {
+    app_handle
+        .state::<AppState>()
+        .is_clipboard_monitoring
+        .load(Ordering::SeqCst)
+}
+
+pub fn current_status(app_handle: &AppHandle) -> CaptureStatus {
+    let state = app_handle.state::<AppState>();
+    let resume_at = state.capture_resume_at.load(Ordering::SeqCst);
+
+    if state.is_clipboard_monitoring.load(Ordering::SeqCst) {
+        CaptureStatus {
+            status: "ready".to_string(),
+            message: "Clipboard monitoring active".to_string(),
+            resume_at: None,
+        }
+    } else if resume_at > 0 {
+        let minutes_left = resume_at.saturating_sub(unix_timestamp()).div_ceil(60);
+        CaptureStatus {
+            status: "incognito".to_string(),
+            message: format!("Incognito, capture resumes in {} min", minutes_left),
+            resume_at: Some(resume_at),
+        }
+    } else {
+        CaptureStatus {
+            status: "paused".to_string(),
+            message: "Clipboard capture paused".to_string(),
+            resume_at: None,
+        }
+    }
+}
+
+/// Stops capture until `resume`, or for `duration` (incognito) after which
+/// it resumes on its own.
+pub fn pause(app_handle: &AppHandle, duration: Option<Duration>) -> CaptureStatus {
+    let state = app_handle.state::<AppState>();
+    let resume_at = duration.map_or(0, |d| unix_timestamp() + d.as_secs().max(1));
+
+    state.is_clipboard_monitoring.store(false, Ordering::SeqCst);
+    state.capture_resume_at.store(resume_at, Ordering::SeqCst);
+
+    if let Some(duration) = duration {
+        let app_handle = app_handle.clone();
+        async_runtime::spawn(async move {
+            tokio::time::sleep(duration).await;
+
+            // A later pause/resume/incognito replaced this timer
+            let state = app_handle.state::<AppState>();
+            if state.capture_resume_at.load(Ordering::SeqCst) == resume_at {
+                println!("⏰ Incognito period over, resuming clipboard capture");
+                resume(&app_handle);
+            }
+        });
+    }
+
+    apply(app_handle)
+}
+
+pub fn resume(app_handle: &AppHandle) -> CaptureStatus {
+    let state = app_handle.state::<AppState>();
+    state.capture_resume_at.store(0, Ordering::SeqCst);
+    state.is_clipboard_monitoring.store(true, Ordering::SeqCst);
+
+    apply(app_handle)
+}
+
+/// Publishes the current state to the tray and the frontend.
+fn apply(app_handle: &AppHandle) -> CaptureStatus {
+    let status = current_status(app_handle);
+    println!("⏯️ Clipboard capture: {}", status.message);
+
+    if let Some(menu) = app_handle.try_state::<TrayCaptureMenu>() {
+        let label = if status.status == "ready" { "Pause capture" } else { "Resume capture" };
+        let _ = menu.pause.set_text(label);
+    }
+
+    if let Some(tray) = app_handle.tray_by_id(crate::TRAY_ID) {
+        let _ = tray.set_tooltip(Some(format!("ClipTray — {}", status.message)));
+    }
+
+    if let Err(e) = app_handle.emit("clipboard-status", &status) {
+        eprintln!("❌ Failed to emit clipboard status: {}", e);
+    }
+
+    status
+}
+
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
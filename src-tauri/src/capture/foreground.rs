@@ -0,0 +1,386 @@
+// src/capture/foreground.rs
+//
+// Which app a copy came from on Linux. Under X11 the window manager publishes
+// the focused window as `_NET_ACTIVE_WINDOW`; its `_NET_WM_PID` leads to the
+// executable, with `WM_CLASS` as fallback. Wayland has no general equivalent;
+// wlroots compositors (Sway, Hyprland, labwc, ...) expose the focused toplevel
+// through wlr-foreign-toplevel-management, which we track on a background
+// thread. Anywhere else the app stays "Unknown".
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowDetection {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    Win32,
+    X11ActiveWindow,
+    WlrForeignToplevel,
+    Unavailable,
+}
+
+#[cfg(target_os = "windows")]
+pub fn detection_method() -> WindowDetection {
+    WindowDetection::Win32
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn detection_method() -> WindowDetection {
+    WindowDetection::Unavailable
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::{active_window, detection_method};
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::sync::Mutex;
+
+    use once_cell::sync::Lazy;
+
+    use super::{wayland, x11, WindowDetection};
+
+    enum Detector {
+        Wayland(wayland::ActiveSlot),
+        // Boxed: an X11 connection is much larger than the other variants
+        X11(Box<Mutex<Option<x11::Session>>>),
+        Unavailable,
+    }
+
+    static DETECTOR: Lazy<Detector> = Lazy::new(|| {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            // XWayland's _NET_ACTIVE_WINDOW goes stale whenever a native
+            // Wayland window has focus, so don't fall back to X11 here
+            return match wayland::spawn() {
+                Ok(active) => Detector::Wayland(active),
+                Err(e) => {
+                    eprintln!("ℹ️ Wayland foreign-toplevel unavailable: {}", e);
+                    Detector::Unavailable
+                }
+            };
+        }
+
+        if std::env::var_os("DISPLAY").is_some() {
+            match x11::Session::connect() {
+                Ok(session) => return Detector::X11(Box::new(Mutex::new(Some(session)))),
+                Err(e) => eprintln!("ℹ️ X11 active window unavailable: {}", e),
+            }
+        }
+
+        Detector::Unavailable
+    });
+
+    pub fn detection_method() -> WindowDetection {
+        match &*DETECTOR {
+            Detector::Wayland(_) => WindowDetection::WlrForeignToplevel,
+            Detector::X11(_) => WindowDetection::X11ActiveWindow,
+            Detector::Unavailable => WindowDetection::Unavailable,
+        }
+    }
+
+    /// `(app name, window title)` of the focused window.
+    pub fn active_window() -> Option<(String, String)> {
+        match &*DETECTOR {
+            Detector::Wayland(active) => active.lock().unwrap().clone(),
+            Detector::X11(session) => {
+                let mut session = session.lock().unwrap();
+                if session.is_none() {
+                    *session = x11::Session::connect().ok();
+                }
+
+                match session.as_ref()?.active_window() {
+                    Ok(window) => window,
+                    Err(e) => {
+                        // Reconnect on the next copy
+                        eprintln!("⚠️ X11 active window lookup failed: {}", e);
+                        *session = None;
+                        None
+                    }
+                }
+            }
+            Detector::Unavailable => None,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::path::Path;
+
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_ACTIVE_WINDOW,
+            _NET_WM_PID,
+            _NET_WM_NAME,
+            UTF8_STRING,
+        }
+    }
+
+    pub struct Session {
+        conn: RustConnection,
+        root: Window,
+        atoms: Atoms,
+    }
+
+    impl Session {
+        pub fn connect() -> Result<Self, Box<dyn std::error::Error>> {
+            let (conn, screen_num) = x11rb::connect(None)?;
+            let root = conn.setup().roots[screen_num].root;
+            let atoms = Atoms::new(&conn)?.reply()?;
+
+            Ok(Self { conn, root, atoms })
+        }
+
+        pub fn active_window(&self) -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+            let window = self
+                .conn
+                .get_property(false, self.root, self.atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW, 0, 1)?
+                .reply()?
+                .value32()
+                .and_then(|mut values| values.next())
+                .filter(|&window| window != x11rb::NONE);
+
+            let Some(window) = window else {
+                return Ok(None);
+            };
+
+            let title = self
+                .text_property(window, self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING)?
+                .or(self.text_property(window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into())?)
+                .unwrap_or_default();
+
+            let app = match self.executable_name(window)? {
+                Some(name) => name,
+                None => match self.wm_class(window)? {
+                    Some(class) => class,
+                    None => return Ok(None),
+                },
+            };
+
+            Ok(Some((app, title)))
+        }
+
+        /// File name of the process behind `_NET_WM_PID`. Only meaningful
+        /// for local clients, which is all a desktop session has.
+        fn executable_name(&self, window: Window) -> Result<Option<String>, Box<dyn std::error::Error>> {
+            let pid = self
+                .conn
+                .get_property(false, window, self.atoms._NET_WM_PID, AtomEnum::CARDINAL, 0, 1)?
+                .reply()?
+                .value32()
+                .and_then(|mut values| values.next());
+
+            let Some(pid) = pid else {
+                return Ok(None);
+            };
+
+            let proc_dir = Path::new("/proc").join(pid.to_string());
+            let name = std::fs::read_link(proc_dir.join("exe"))
+                .ok()
+                .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .or_else(|| {
+                    std::fs::read_to_string(proc_dir.join("comm"))
+                        .ok()
+                        .map(|comm| comm.trim().to_string())
+                })
+                .filter(|name| !name.is_empty());
+
+            Ok(name)
+        }
+
+        /// Class part of `WM_CLASS` ("instance\0class\0").
+        fn wm_class(&self, window: Window) -> Result<Option<String>, Box<dyn std::error::Error>> {
+            let value = self
+                .conn
+                .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)?
+                .reply()?
+                .value;
+
+            let class = String::from_utf8_lossy(&value)
+                .split('\0')
+                .rfind(|part| !part.is_empty())
+                .map(str::to_string);
+
+            Ok(class)
+        }
+
+        fn text_property(
+            &self,
+            window: Window,
+            property: u32,
+            type_: u32,
+        ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+            let value = self
+                .conn
+                .get_property(false, window, property, type_, 0, 1024)?
+                .reply()?
+                .value;
+
+            Ok((!value.is_empty()).then(|| String::from_utf8_lossy(&value).into_owned()))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod wayland {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use wayland_client::backend::ObjectId;
+    use wayland_client::protocol::wl_registry;
+    use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle, WEnum};
+    use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+        zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+        zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+    };
+
+    #[derive(Default)]
+    struct Toplevel {
+        app_id: String,
+        title: String,
+        activated: bool,
+    }
+
+    /// Focused toplevel as `(app_id, title)`, shared with the tracker thread.
+    pub type ActiveSlot = Arc<Mutex<Option<(String, String)>>>;
+
+    struct TrackerState {
+        manager: Option<ZwlrForeignToplevelManagerV1>,
+        toplevels: HashMap<ObjectId, Toplevel>,
+        active_id: Option<ObjectId>,
+        active: ActiveSlot,
+    }
+
+    /// Starts tracking toplevels; the returned slot always holds the focused
+    /// one as `(app_id, title)`.
+    pub fn spawn() -> Result<ActiveSlot, Box<dyn std::error::Error>> {
+        let conn = Connection::connect_to_env()?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        let active = Arc::new(Mutex::new(None));
+        let mut state = TrackerState {
+            manager: None,
+            toplevels: HashMap::new(),
+            active_id: None,
+            active: active.clone(),
+        };
+        queue.roundtrip(&mut state)?;
+
+        if state.manager.is_none() {
+            return Err("compositor does not support zwlr_foreign_toplevel_manager_v1".into());
+        }
+        queue.roundtrip(&mut state)?;
+
+        std::thread::Builder::new()
+            .name("foreground-wayland-tracker".into())
+            .spawn(move || loop {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    eprintln!("❌ Wayland foreground tracker stopped: {}", e);
+                    *state.active.lock().unwrap() = None;
+                    break;
+                }
+            })?;
+
+        Ok(active)
+    }
+
+    impl TrackerState {
+        fn publish(&self) {
+            let active = self
+                .active_id
+                .as_ref()
+                .and_then(|id| self.toplevels.get(id))
+                .map(|t| (t.app_id.clone(), t.title.clone()));
+            *self.active.lock().unwrap() = active;
+        }
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for TrackerState {
+        fn event(
+            state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global { name, interface, version } = event {
+                if interface == ZwlrForeignToplevelManagerV1::interface().name {
+                    state.manager = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for TrackerState {
+        fn event(
+            state: &mut Self,
+            _: &ZwlrForeignToplevelManagerV1,
+            event: zwlr_foreign_toplevel_manager_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+                state.toplevels.insert(toplevel.id(), Toplevel::default());
+            }
+        }
+
+        event_created_child!(TrackerState, ZwlrForeignToplevelManagerV1, [
+            zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+        ]);
+    }
+
+    impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for TrackerState {
+        fn event(
+            state: &mut Self,
+            handle: &ZwlrForeignToplevelHandleV1,
+            event: zwlr_foreign_toplevel_handle_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            use zwlr_foreign_toplevel_handle_v1::{Event, State};
+
+            let id = handle.id();
+            match event {
+                Event::Title { title } => {
+                    state.toplevels.entry(id).or_default().title = title;
+                }
+                Event::AppId { app_id } => {
+                    state.toplevels.entry(id).or_default().app_id = app_id;
+                }
+                Event::State { state: flags } => {
+                    let activated = flags
+                        .chunks_exact(4)
+                        .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                        .any(|flag| matches!(WEnum::<State>::from(flag), WEnum::Value(State::Activated)));
+                    state.toplevels.entry(id).or_default().activated = activated;
+                }
+                Event::Done => {
+                    let activated = state.toplevels.get(&id).is_some_and(|t| t.activated);
+                    if activated {
+                        state.active_id = Some(id);
+                    } else if state.active_id.as_ref() == Some(&id) {
+                        state.active_id = None;
+                    }
+                    state.publish();
+                }
+                Event::Closed => {
+                    state.toplevels.remove(&id);
+                    if state.active_id.as_ref() == Some(&id) {
+                        state.active_id = None;
+                        state.publish();
+                    }
+                    handle.destroy();
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// src/capture/hints.rs
+//
+// Password managers and some generators tag what they put on the clipboard
+// with extra marker formats, asking clipboard managers not to keep it:
+//   - Windows: `ExcludeClipboardContentFromMonitorProcessing`,
+//     `Clipboard Viewer Ignore`, and `CanIncludeInClipboardHistory` set to 0
+//   - X11: `x-kde-passwordManagerHint` (KeePassXC, KDE Wallet) and the
+//     nspasteboard.org types some cross-platform apps also offer there
+// Only the presence of the marker matters, never the copied content itself.
+// Pure Wayland sessions and macOS report no hints.
+
+/// nspasteboard.org markers for secrets, and the X11 password manager hint.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const CONCEALED_TARGETS: &[&str] = &["x-kde-passwordManagerHint", "org.nspasteboard.ConcealedType"];
+
+/// nspasteboard.org markers for short-lived or app-generated content.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const TRANSIENT_TARGETS: &[&str] = &["org.nspasteboard.TransientType", "org.nspasteboard.AutoGeneratedType"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClipboardHints {
+    /// Marked as a secret, usually by a password manager
+    pub concealed: bool,
+    /// Marked as short-lived or generated, not something the user copied
+    pub transient: bool,
+}
+
+impl ClipboardHints {
+    /// Whether the owner asked for this content to stay out of history.
+    pub fn keep_out_of_history(&self) -> bool {
+        self.concealed || self.transient
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn from_targets(targets: &[String]) -> Self {
+        let offered = |names: &[&str]| targets.iter().any(|t| names.contains(&t.as_str()));
+        Self {
+            concealed: offered(CONCEALED_TARGETS),
+            transient: offered(TRANSIENT_TARGETS),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn read_system_hints() -> Result<ClipboardHints, String> {
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::{IsClipboardFormatAvailable, RegisterClipboardFormatW};
+
+    unsafe {
+        let concealed = [
+            RegisterClipboardFormatW(w!("ExcludeClipboardContentFromMonitorProcessing")),
+            RegisterClipboardFormatW(w!("Clipboard Viewer Ignore")),
+        ]
+        .into_iter()
+        .any(|format| format != 0 && IsClipboardFormatAvailable(format).is_ok());
+
+        let history = RegisterClipboardFormatW(w!("CanIncludeInClipboardHistory"));
+        let no_history = history != 0
+            && IsClipboardFormatAvailable(history).is_ok()
+            && windows_dword_format(history) == Some(0);
+
+        Ok(ClipboardHints {
+            concealed: concealed || no_history,
+            transient: false,
+        })
+    }
+}
+
+/// Value of a clipboard format holding a single DWORD.
+#[cfg(target_os = "windows")]
+unsafe fn windows_dword_format(format: u32) -> Option<u32> {
+    use windows::Win32::Foundation::{HGLOBAL, HWND};
+    use windows::Win32::System::DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard};
+    use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+
+    // The owner may still be holding the clipboard open; don't wait for it
+    OpenClipboard(HWND::default()).ok()?;
+
+    let value = GetClipboardData(format).ok().and_then(|handle| {
+        let global = HGLOBAL(handle.0);
+        if GlobalSize(global) < std::mem::size_of::<u32>() {
+            return None;
+        }
+
+        let data = GlobalLock(global) as *const u32;
+        if data.is_null() {
+            return None;
+        }
+        let value = data.read_unaligned();
+        let _ = GlobalUnlock(global);
+        Some(value)
+    });
+
+    let _ = CloseClipboard();
+    value
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_system_hints() -> Result<ClipboardHints, String> {
+    // XWayland also bridges the formats offered by Wayland clients
+    if std::env::var_os("DISPLAY").is_none() {
+        return Ok(ClipboardHints::default());
+    }
+
+    x11::clipboard_hint_targets()
+        .map(|targets| ClipboardHints::from_targets(&targets))
+        .map_err(|e| format!("Failed to read clipboard targets: {}", e))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn read_system_hints() -> Result<ClipboardHints, String> {
+    Ok(ClipboardHints::default())
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::time::{Duration, Instant};
+
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, WindowClass};
+    use x11rb::protocol::Event;
+
+    use super::{CONCEALED_TARGETS, TRANSIENT_TARGETS};
+
+    /// How long the clipboard owner gets to answer the TARGETS request.
+    const REPLY_TIMEOUT: Duration = Duration::from_millis(200);
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            CLIPBOARD,
+            TARGETS,
+            CLIPTRAY_TARGETS,
+        }
+    }
+
+    /// Hint formats the current CLIPBOARD owner offers.
+    pub fn clipboard_hint_targets() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+
+        // An atom nobody ever interned can't be on offer; in the common case
+        // this answers without talking to the clipboard owner at all
+        let mut hints: Vec<(Atom, &str)> = Vec::new();
+        for &name in CONCEALED_TARGETS.iter().chain(TRANSIENT_TARGETS) {
+            let atom = conn.intern_atom(true, name.as_bytes())?.reply()?.atom;
+            if atom != x11rb::NONE {
+                hints.push((atom, name));
+            }
+        }
+        if hints.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let atoms = Atoms::new(&conn)?.reply()?;
+        let root = conn.setup().roots[screen_num].root;
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+        conn.convert_selection(window, atoms.CLIPBOARD, atoms.TARGETS, atoms.CLIPTRAY_TARGETS, x11rb::CURRENT_TIME)?;
+        conn.flush()?;
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        let mut offered: Vec<Atom> = Vec::new();
+        while Instant::now() < deadline {
+            match conn.poll_for_event()? {
+                Some(Event::SelectionNotify(event)) if event.requestor == window => {
+                    if event.property != x11rb::NONE {
+                        offered = conn
+                            .get_property(true, window, atoms.CLIPTRAY_TARGETS, AtomEnum::ATOM, 0, 1024)?
+                            .reply()?
+                            .value32()
+                            .map(|values| values.collect())
+                            .unwrap_or_default();
+                    }
+                    break;
+                }
+                Some(_) => {}
+                None => std::thread::sleep(Duration::from_millis(5)),
+            }
+        }
+
+        conn.destroy_window(window)?;
+        conn.flush()?;
+
+        Ok(hints
+            .into_iter()
+            .filter(|(atom, _)| offered.contains(atom))
+            .map(|(_, name)| name.to_string())
+            .collect())
+    }
+}
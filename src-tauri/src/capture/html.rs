@@ -0,0 +1,32 @@
+// src/capture/html.rs
+//
+// Rich-text copies (browsers, office apps) carry an HTML flavor next to the
+// plain text. The raw markup is stored so paste-back is faithful; anything
+// handed to the webview goes through `sanitize` first.
+use serde::Serializer;
+
+/// HTML larger than this (whole pages, big spreadsheets) is dropped and the
+/// copy is kept as plain text only.
+const MAX_HTML_BYTES: usize = 1024 * 1024;
+
+/// Strips scripts, event handlers and anything else that could run in the
+/// webview, keeping ordinary formatting markup.
+pub fn sanitize(html: &str) -> String {
+    ammonia::clean(html)
+}
+
+/// The HTML flavor worth storing alongside a text copy, if any.
+pub fn usable_html(html: Option<String>) -> Option<String> {
+    html.filter(|html| !html.trim().is_empty() && html.len() <= MAX_HTML_BYTES)
+}
+
+/// `serialize_with` helper for HTML fields that end up in the webview.
+pub fn serialize_sanitized<S: Serializer>(
+    html: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match html {
+        Some(html) => serializer.serialize_some(&sanitize(html)),
+        None => serializer.serialize_none(),
+    }
+}
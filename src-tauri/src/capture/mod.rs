@@ -0,0 +1,14 @@
+// src/capture/mod.rs
+pub mod classifier;
+pub mod control;
+pub mod foreground;
+pub mod hints;
+pub mod html;
+pub mod paste;
+pub mod pipeline;
+pub mod primary;
+pub mod rules;
+pub mod secrets;
+pub mod settle;
+pub mod source;
+pub mod watcher;
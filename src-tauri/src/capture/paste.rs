@@ -0,0 +1,127 @@
+// src/capture/paste.rs
+//
+// Writing history entries back to the system clipboard. The write itself is
+// done by the monitor loop (through the pipeline's own clipboard source), so
+// the pipeline knows the new contents are ours and never captures them as a
+// fresh copy. Commands reach the loop through `ClipboardWriter`.
+use std::path::PathBuf;
+use std::time::Duration;
+
+use arboard::ImageData;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long the previously focused window gets to take focus back after our
+/// popup hides, before the paste keystroke is sent.
+const FOCUS_RETURN_DELAY: Duration = Duration::from_millis(150);
+
+pub enum PasteContent {
+    /// Plain text, plus the HTML flavor when the entry was rich text
+    Text { text: String, html: Option<String> },
+    Image(ImageData<'static>),
+    Files(Vec<PathBuf>),
+}
+
+pub struct WriteRequest {
+    pub content: PasteContent,
+    pub done: oneshot::Sender<Result<(), String>>,
+}
+
+/// Managed state: hands write requests to the running monitor loop.
+pub struct ClipboardWriter {
+    requests: mpsc::UnboundedSender<WriteRequest>,
+}
+
+impl ClipboardWriter {
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<WriteRequest>) {
+        let (requests, rx) = mpsc::unbounded_channel();
+        (Self { requests }, rx)
+    }
+
+    pub async fn write(&self, content: PasteContent) -> Result<(), String> {
+        let (done, result) = oneshot::channel();
+
+        self.requests
+            .send(WriteRequest { content, done })
+            .map_err(|_| "Clipboard monitoring is not running".to_string())?;
+
+        result
+            .await
+            .map_err(|_| "Clipboard monitoring stopped before writing".to_string())?
+    }
+}
+
+/// Sends Ctrl+V to whichever window has focus once ours is out of the way.
+pub async fn simulate_paste() -> Result<(), String> {
+    tokio::time::sleep(FOCUS_RETURN_DELAY).await;
+
+    tokio::task::spawn_blocking(send_paste_keystroke)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(target_os = "linux")]
+fn send_paste_keystroke() -> Result<(), String> {
+    // XTest only reaches X11 clients; under Wayland the focused window is
+    // usually native and would never see the keystroke.
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return Err("Paste keystroke is not supported on Wayland".to_string());
+    }
+
+    x11::send_ctrl_v().map_err(|e| format!("Failed to send paste keystroke: {}", e))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_paste_keystroke() -> Result<(), String> {
+    Err("Paste keystroke is only supported on X11".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        ConnectionExt as _, Keycode, Keysym, KEY_PRESS_EVENT, KEY_RELEASE_EVENT,
+    };
+    use x11rb::protocol::xtest::ConnectionExt as _;
+
+    const XK_CONTROL_L: Keysym = 0xffe3;
+    const XK_V: Keysym = 0x0076;
+
+    pub fn send_ctrl_v() -> Result<(), Box<dyn std::error::Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        conn.xtest_get_version(2, 2)?.reply()?;
+
+        let root = conn.setup().roots[screen_num].root;
+        let control = keycode_for(&conn, XK_CONTROL_L)?;
+        let v = keycode_for(&conn, XK_V)?;
+
+        for (event, keycode) in [
+            (KEY_PRESS_EVENT, control),
+            (KEY_PRESS_EVENT, v),
+            (KEY_RELEASE_EVENT, v),
+            (KEY_RELEASE_EVENT, control),
+        ] {
+            conn.xtest_fake_input(event, keycode, x11rb::CURRENT_TIME, root, 0, 0, 0)?;
+        }
+
+        // Round trip so the events are processed before the connection drops
+        conn.get_input_focus()?.reply()?;
+        Ok(())
+    }
+
+    fn keycode_for(
+        conn: &impl Connection,
+        keysym: Keysym,
+    ) -> Result<Keycode, Box<dyn std::error::Error>> {
+        let setup = conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = conn.get_keyboard_mapping(min, max - min + 1)?.reply()?;
+        let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+
+        mapping
+            .keysyms
+            .chunks(per_keycode)
+            .position(|syms| syms.contains(&keysym))
+            .map(|index| min + index as Keycode)
+            .ok_or_else(|| format!("No keycode for keysym {:#x}", keysym).into())
+    }
+}
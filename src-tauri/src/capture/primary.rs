@@ -0,0 +1,121 @@
+// src/capture/primary.rs
+//
+// The X11/Wayland PRIMARY selection changes with every mouse movement while
+// text is being drag-selected, and nothing announces when the drag is over.
+// The monitor polls it instead, and `PrimaryTracker` only lets a value
+// through once it has stopped changing for the configured debounce.
+use std::time::{Duration, Instant};
+
+/// How often PRIMARY is read while primary capture or sync is enabled.
+pub const PRIMARY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+pub struct PrimaryTracker {
+    /// Latest value that isn't settled yet, and since when it is unchanged
+    pending: Option<(String, Instant)>,
+    /// Last value handed out or written by us
+    last_seen: String,
+}
+
+impl PrimaryTracker {
+    /// Feeds the current PRIMARY text. Returns it once it has been unchanged
+    /// for `debounce`, and only once per distinct value.
+    pub fn observe(&mut self, current: Option<String>, debounce: Duration) -> Option<String> {
+        // An empty selection (click without drag) keeps whatever was pending
+        let current = current.filter(|text| !text.trim().is_empty())?;
+
+        if current == self.last_seen {
+            self.pending = None;
+            return None;
+        }
+
+        match &self.pending {
+            Some((text, since)) if *text == current => {
+                if since.elapsed() < debounce {
+                    return None;
+                }
+                self.mark_seen(&current);
+                Some(current)
+            }
+            _ => {
+                self.pending = Some((current, Instant::now()));
+                None
+            }
+        }
+    }
+
+    /// Treats `text` as already handled (paused capture, or we wrote it).
+    pub fn mark_seen(&mut self, text: &str) {
+        self.last_seen = text.to_string();
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG: Duration = Duration::from_secs(60);
+
+    fn some(text: &str) -> Option<String> {
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn value_is_released_once_settled() {
+        let mut tracker = PrimaryTracker::default();
+
+        assert_eq!(tracker.observe(some("hello"), Duration::ZERO), None);
+        assert_eq!(tracker.observe(some("hello"), Duration::ZERO), some("hello"));
+        // Only once per distinct value
+        assert_eq!(tracker.observe(some("hello"), Duration::ZERO), None);
+    }
+
+    #[test]
+    fn changing_selection_is_held_back() {
+        let mut tracker = PrimaryTracker::default();
+
+        assert_eq!(tracker.observe(some("he"), LONG), None);
+        assert_eq!(tracker.observe(some("hell"), LONG), None);
+        assert_eq!(tracker.observe(some("hello"), LONG), None);
+        assert_eq!(tracker.observe(some("hello"), LONG), None);
+    }
+
+    #[test]
+    fn selection_change_restarts_the_debounce() {
+        let mut tracker = PrimaryTracker::default();
+        let debounce = Duration::from_millis(20);
+
+        tracker.observe(some("first"), debounce);
+        std::thread::sleep(debounce);
+        assert_eq!(tracker.observe(some("second"), debounce), None);
+        assert_eq!(tracker.observe(some("second"), debounce), None);
+
+        std::thread::sleep(debounce);
+        assert_eq!(tracker.observe(some("second"), debounce), some("second"));
+    }
+
+    #[test]
+    fn empty_selection_keeps_the_pending_value() {
+        let mut tracker = PrimaryTracker::default();
+
+        tracker.observe(some("hello"), Duration::ZERO);
+        assert_eq!(tracker.observe(None, Duration::ZERO), None);
+        assert_eq!(tracker.observe(some("  "), Duration::ZERO), None);
+        assert_eq!(tracker.observe(some("hello"), Duration::ZERO), some("hello"));
+    }
+
+    #[test]
+    fn marked_values_are_not_released() {
+        let mut tracker = PrimaryTracker::default();
+
+        tracker.observe(some("ours"), Duration::ZERO);
+        tracker.mark_seen("ours");
+        assert_eq!(tracker.observe(some("ours"), Duration::ZERO), None);
+        assert_eq!(tracker.observe(some("ours"), Duration::ZERO), None);
+
+        // A new selection after ours goes through as usual
+        tracker.observe(some("theirs"), Duration::ZERO);
+        assert_eq!(tracker.observe(some("theirs"), Duration::ZERO), some("theirs"));
+    }
+}
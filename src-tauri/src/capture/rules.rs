@@ -0,0 +1,238 @@
+// src/capture/rules.rs
+//
+// User-defined capture rules, evaluated on every new entry right before it
+// is saved. Every enabled rule whose conditions all match contributes its
+// actions; tags accumulate and pin/sensitive are sticky once any rule sets
+// them.
+//
+// Patterns are compiled once per rule set and cached per organization until
+// a rule is saved, deleted or synced.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sqlx::SqlitePool;
+
+use crate::db::schemas::capture_rules::{CaptureRule, RuleConditions};
+use crate::db::schemas::NewClipboardEntry;
+use crate::db::sqlite_capture_rules_repository::SqliteCaptureRulesRepository;
+
+static RULE_SETS: Lazy<RwLock<HashMap<String, Arc<Vec<CompiledRule>>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+/// Bumped on every invalidation so a load that raced a save is not cached.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// An enabled rule with its content pattern compiled.
+pub struct CompiledRule {
+    pub rule: CaptureRule,
+    pattern: Option<Regex>,
+}
+
+impl CompiledRule {
+    /// `None` for disabled rules and for stored patterns that no longer
+    /// compile; neither can ever match.
+    pub fn new(rule: CaptureRule) -> Option<Self> {
+        if !rule.enabled {
+            return None;
+        }
+        match rule.conditions.compile_pattern() {
+            Ok(pattern) => Some(Self { rule, pattern }),
+            Err(e) => {
+                eprintln!("⚠️ Skipping capture rule '{}': {}", rule.name, e);
+                None
+            }
+        }
+    }
+}
+
+/// The organization's enabled rules, compiled; loaded on first use.
+pub async fn load(pool: &SqlitePool, organization_id: &str) -> Result<Arc<Vec<CompiledRule>>, sqlx::Error> {
+    if let Some(rules) = RULE_SETS.read().ok().and_then(|sets| sets.get(organization_id).cloned()) {
+        return Ok(rules);
+    }
+
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let rules = SqliteCaptureRulesRepository::get_for_org(pool, organization_id).await?;
+    let rules = Arc::new(rules.into_iter().filter_map(CompiledRule::new).collect::<Vec<_>>());
+
+    if let Ok(mut sets) = RULE_SETS.write() {
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            sets.insert(organization_id.to_string(), Arc::clone(&rules));
+        }
+    }
+    Ok(rules)
+}
+
+/// Drops the cached rule set after the organization's rules changed.
+pub fn invalidate(organization_id: &str) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut sets) = RULE_SETS.write() {
+        sets.remove(organization_id);
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RuleOutcome {
+    /// Names of the rules that matched, for logging
+    pub matched: Vec<String>,
+    pub add_tags: Vec<String>,
+    pub pin: bool,
+    pub mark_sensitive: bool,
+}
+
+pub fn evaluate(rules: &[CompiledRule], entry: &NewClipboardEntry) -> RuleOutcome {
+    let types = entry_types(entry);
+    let mut outcome = RuleOutcome::default();
+
+    for CompiledRule { rule, pattern } in rules {
+        if !matches(&rule.conditions, pattern.as_ref(), entry, &types) {
+            continue;
+        }
+
+        outcome.matched.push(rule.name.clone());
+        for tag in &rule.actions.add_tags {
+            if !outcome.add_tags.contains(tag) {
+                outcome.add_tags.push(tag.clone());
+            }
+        }
+        outcome.pin |= rule.actions.pin;
+        outcome.mark_sensitive |= rule.actions.mark_sensitive;
+    }
+
+    outcome
+}
+
+fn matches(
+    conditions: &RuleConditions,
+    pattern: Option<&Regex>,
+    entry: &NewClipboardEntry,
+    types: &[String],
+) -> bool {
+    // A rule with nothing to check would match every copy
+    if conditions.is_empty() {
+        return false;
+    }
+
+    let contains = |haystack: &str, needle: &Option<String>| {
+        needle
+            .as_ref()
+            .is_none_or(|needle| haystack.to_lowercase().contains(&needle.to_lowercase()))
+    };
+
+    if !contains(&entry.source_app, &conditions.source_app)
+        || !contains(&entry.source_window, &conditions.source_window)
+    {
+        return false;
+    }
+
+    if let Some(content_type) = &conditions.content_type {
+        if !types.iter().any(|t| t.eq_ignore_ascii_case(content_type)) {
+            return false;
+        }
+    }
+
+    pattern.is_none_or(|regex| regex.is_match(&entry.content))
+}
+
+/// Primary type plus every secondary type the classifier recorded.
+fn entry_types(entry: &NewClipboardEntry) -> Vec<String> {
+    let mut types = vec![entry.content_type.clone()];
+
+    let secondary = entry
+        .content_meta
+        .as_deref()
+        .and_then(|meta| serde_json::from_str::<serde_json::Value>(meta).ok())
+        .and_then(|meta| meta.get("types").cloned())
+        .and_then(|types| serde_json::from_value::<Vec<String>>(types).ok())
+        .unwrap_or_default();
+
+    types.extend(secondary);
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schemas::capture_rules::RuleActions;
+    use chrono::Utc;
+
+    fn rule(name: &str, conditions: RuleConditions, tag: &str) -> CaptureRule {
+        CaptureRule {
+            id: 0,
+            organization_id: "org".to_string(),
+            name: name.to_string(),
+            enabled: true,
+            position: 0,
+            conditions,
+            actions: RuleActions { add_tags: vec![tag.to_string()], ..Default::default() },
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn pattern(pattern: &str) -> RuleConditions {
+        RuleConditions { content_pattern: Some(pattern.to_string()), ..Default::default() }
+    }
+
+    fn entry(content: &str) -> NewClipboardEntry {
+        NewClipboardEntry::from_monitoring_data(content.to_string(), "Terminal".to_string(), "zsh".to_string())
+    }
+
+    #[test]
+    fn compiled_pattern_matches_content() {
+        let rules: Vec<_> = [rule("tickets", pattern(r"^[A-Z]+-\d+$"), "ticket")]
+            .into_iter()
+            .filter_map(CompiledRule::new)
+            .collect();
+
+        assert_eq!(evaluate(&rules, &entry("ABC-123")).add_tags, vec!["ticket"]);
+        assert!(evaluate(&rules, &entry("abc 123")).matched.is_empty());
+    }
+
+    #[test]
+    fn invalid_and_disabled_rules_are_dropped() {
+        let mut disabled = rule("off", pattern("."), "off");
+        disabled.enabled = false;
+
+        assert!(CompiledRule::new(rule("broken", pattern("(unclosed"), "x")).is_none());
+        assert!(CompiledRule::new(disabled).is_none());
+    }
+
+    #[test]
+    fn other_conditions_apply_alongside_the_pattern() {
+        let conditions = RuleConditions {
+            source_app: Some("term".to_string()),
+            ..pattern("secret")
+        };
+        let rules: Vec<_> = CompiledRule::new(rule("term", conditions, "t")).into_iter().collect();
+
+        assert_eq!(evaluate(&rules, &entry("my secret")).matched, vec!["term"]);
+
+        let mut elsewhere = entry("my secret");
+        elsewhere.source_app = "Browser".to_string();
+        assert!(evaluate(&rules, &elsewhere).matched.is_empty());
+    }
+
+    #[tokio::test]
+    async fn saved_rules_are_picked_up_after_invalidate() {
+        let pool = crate::db::sqlite_database::memory_pool().await;
+        let org = "rules-cache-org";
+        assert!(load(&pool, org).await.unwrap().is_empty());
+
+        let new_rule = crate::db::schemas::capture_rules::NewCaptureRule {
+            name: "urls".to_string(),
+            enabled: true,
+            position: 0,
+            conditions: pattern("^https://"),
+            actions: RuleActions { pin: true, ..Default::default() },
+        };
+        SqliteCaptureRulesRepository::create(&pool, org, &new_rule).await.unwrap();
+        assert!(load(&pool, org).await.unwrap().is_empty());
+
+        invalidate(org);
+        let rules = load(&pool, org).await.unwrap();
+        assert!(evaluate(&rules, &entry("https://example.com")).pin);
+    }
+}
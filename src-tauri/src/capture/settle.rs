@@ -0,0 +1,155 @@
+// src/capture/settle.rs
+//
+// IDEs, remote-desktop clients and some terminals rewrite the clipboard
+// several times a second. Instead of reading it on every change
+// notification, the monitor waits until notifications have stopped for the
+// organization's settle window, so only the final value of a burst is saved.
+// Wakes that find the clipboard unchanged (polling ticks, duplicate
+// notifications) don't count as changes.
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// A client that never stops writing still gets its latest value recorded
+/// this often.
+const MAX_SETTLE_DELAY: Duration = Duration::from_secs(5);
+
+/// The copy waiting for its burst to end. Source app and window are the ones
+/// in front at the latest change.
+#[derive(Debug)]
+pub struct PendingCopy {
+    pub source_app: String,
+    pub source_window: String,
+    /// Change notifications folded into this copy
+    pub changes: u32,
+    first_change: Instant,
+    deadline: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct SettleTimer {
+    pending: Option<PendingCopy>,
+    /// Clipboard fingerprint at the previous wake
+    last_fingerprint: Option<u64>,
+}
+
+impl SettleTimer {
+    /// Records the clipboard fingerprint found at a wake. False when it is
+    /// the same as at the previous wake, so the wake isn't a change.
+    pub fn changed(&mut self, fingerprint: Option<u64>) -> bool {
+        if fingerprint == self.last_fingerprint {
+            return false;
+        }
+        self.last_fingerprint = fingerprint;
+        true
+    }
+
+    /// Notes a clipboard change and pushes the deadline back by `window`.
+    pub fn restart(&mut self, window: Duration, source_app: String, source_window: String) {
+        let now = Instant::now();
+
+        let (first_change, changes) = match &self.pending {
+            Some(pending) => (pending.first_change, pending.changes + 1),
+            None => (now, 1),
+        };
+
+        self.pending = Some(PendingCopy {
+            source_app,
+            source_window,
+            changes,
+            first_change,
+            deadline: (now + window).min(first_change + MAX_SETTLE_DELAY),
+        });
+    }
+
+    /// When the pending copy is due, `None` when nothing is waiting.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|pending| pending.deadline)
+    }
+
+    /// Removes and returns the pending copy, when it is due or cancelled.
+    pub fn take(&mut self) -> Option<PendingCopy> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(300);
+
+    /// What the monitor does on a clipboard wake.
+    fn wake(timer: &mut SettleTimer, fingerprint: u64, window: Duration) -> bool {
+        if !timer.changed(Some(fingerprint)) {
+            return false;
+        }
+        timer.restart(window, "Code".to_string(), "main.rs".to_string());
+        true
+    }
+
+    #[test]
+    fn first_change_starts_the_timer() {
+        let mut timer = SettleTimer::default();
+        assert!(timer.deadline().is_none());
+
+        let before = Instant::now();
+        assert!(wake(&mut timer, 1, WINDOW));
+
+        let deadline = timer.deadline().unwrap();
+        assert!(deadline >= before + WINDOW && deadline <= Instant::now() + WINDOW);
+        assert_eq!(timer.take().unwrap().changes, 1);
+        assert!(timer.deadline().is_none());
+    }
+
+    #[test]
+    fn unchanged_clipboard_does_not_push_the_deadline() {
+        let mut timer = SettleTimer::default();
+        wake(&mut timer, 1, WINDOW);
+        let deadline = timer.deadline();
+
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!wake(&mut timer, 1, WINDOW));
+
+        assert_eq!(timer.deadline(), deadline);
+        assert_eq!(timer.take().unwrap().changes, 1);
+    }
+
+    #[test]
+    fn new_content_pushes_the_deadline_and_keeps_the_latest_source() {
+        let mut timer = SettleTimer::default();
+        wake(&mut timer, 1, WINDOW);
+        let first = timer.deadline().unwrap();
+
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(timer.changed(Some(2)));
+        timer.restart(WINDOW, "Terminal".to_string(), "zsh".to_string());
+
+        assert!(timer.deadline().unwrap() > first);
+        let copy = timer.take().unwrap();
+        assert_eq!(copy.changes, 2);
+        assert_eq!(copy.source_app, "Terminal");
+        assert_eq!(copy.source_window, "zsh");
+    }
+
+    #[test]
+    fn deadline_is_capped_for_endless_bursts() {
+        let mut timer = SettleTimer::default();
+        let before = Instant::now();
+        wake(&mut timer, 1, Duration::from_secs(60));
+        wake(&mut timer, 2, Duration::from_secs(60));
+
+        assert!(timer.deadline().unwrap() <= before + MAX_SETTLE_DELAY + Duration::from_millis(50));
+    }
+
+    #[test]
+    fn same_content_after_settling_is_not_a_new_copy() {
+        let mut timer = SettleTimer::default();
+        wake(&mut timer, 1, WINDOW);
+        timer.take();
+
+        assert!(!wake(&mut timer, 1, WINDOW));
+        assert!(timer.deadline().is_none());
+        assert!(wake(&mut timer, 2, WINDOW));
+    }
+}
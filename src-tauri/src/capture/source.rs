@@ -0,0 +1,279 @@
+// src/capture/source.rs
+//
+// Everything the capture pipeline needs from "the clipboard", behind a trait
+// so the pipeline can run against the real system clipboard or against an
+// in-memory one (headless tests, no display server).
+use std::path::PathBuf;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+
+use arboard::{Clipboard, ImageData};
+
+use crate::capture::hints::{self, ClipboardHints};
+
+pub trait ClipboardSource: Send {
+    /// `Ok(None)` means the clipboard holds no text right now.
+    fn read_text(&mut self) -> Result<Option<String>, String>;
+
+    /// `Ok(None)` means the clipboard holds no HTML flavor right now.
+    fn read_html(&mut self) -> Result<Option<String>, String>;
+
+    /// `Ok(None)` means the clipboard holds no image right now.
+    fn read_image(&mut self) -> Result<Option<ImageData<'static>>, String>;
+
+    /// Paths copied in a file manager; `Ok(None)` when there are none.
+    fn read_files(&mut self) -> Result<Option<Vec<PathBuf>>, String>;
+
+    fn write_text(&mut self, text: &str) -> Result<(), String>;
+
+    /// Writes HTML with `text` as the plain-text fallback.
+    fn write_html(&mut self, html: &str, text: &str) -> Result<(), String>;
+
+    fn write_image(&mut self, image: ImageData<'static>) -> Result<(), String>;
+
+    fn write_files(&mut self, paths: &[PathBuf]) -> Result<(), String>;
+
+    /// Text of the Linux PRIMARY selection; always `Ok(None)` elsewhere.
+    fn read_primary_text(&mut self) -> Result<Option<String>, String>;
+
+    /// Sets the Linux PRIMARY selection; a no-op elsewhere.
+    fn write_primary_text(&mut self, text: &str) -> Result<(), String>;
+
+    /// Concealed/transient markers the clipboard owner attached to the
+    /// current content (see capture::hints).
+    fn read_hints(&mut self) -> Result<ClipboardHints, String>;
+}
+
+/// The OS clipboard through arboard. Reopens the handle after unexpected
+/// errors, which is how arboard recovers from a lost X11/Wayland connection.
+pub struct SystemClipboard {
+    clipboard: Clipboard,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self, arboard::Error> {
+        Ok(Self {
+            clipboard: Clipboard::new()?,
+        })
+    }
+
+    fn map_error<T>(&mut self, result: Result<T, arboard::Error>) -> Result<Option<T>, String> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(arboard::Error::ContentNotAvailable) => Ok(None),
+            Err(e) => {
+                if let Ok(new_clipboard) = Clipboard::new() {
+                    self.clipboard = new_clipboard;
+                    println!("🔄 Clipboard reinitialized");
+                }
+                Err(e.to_string())
+            }
+        }
+    }
+}
+
+impl ClipboardSource for SystemClipboard {
+    fn read_text(&mut self) -> Result<Option<String>, String> {
+        let result = self.clipboard.get_text();
+        self.map_error(result)
+    }
+
+    fn read_html(&mut self) -> Result<Option<String>, String> {
+        let result = self.clipboard.get().html();
+        self.map_error(result)
+    }
+
+    fn read_image(&mut self) -> Result<Option<ImageData<'static>>, String> {
+        let result = self.clipboard.get_image();
+        self.map_error(result)
+    }
+
+    fn read_files(&mut self) -> Result<Option<Vec<PathBuf>>, String> {
+        let result = self.clipboard.get().file_list();
+        Ok(self.map_error(result)?.filter(|paths| !paths.is_empty()))
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<(), String> {
+        self.clipboard.set_text(text).map_err(|e| e.to_string())
+    }
+
+    fn write_html(&mut self, html: &str, text: &str) -> Result<(), String> {
+        self.clipboard
+            .set_html(html, Some(text))
+            .map_err(|e| e.to_string())
+    }
+
+    fn write_image(&mut self, image: ImageData<'static>) -> Result<(), String> {
+        self.clipboard.set_image(image).map_err(|e| e.to_string())
+    }
+
+    fn write_files(&mut self, paths: &[PathBuf]) -> Result<(), String> {
+        self.clipboard
+            .set()
+            .file_list(paths)
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_primary_text(&mut self) -> Result<Option<String>, String> {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+
+        let result = self
+            .clipboard
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text();
+        self.map_error(result)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read_primary_text(&mut self) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn write_primary_text(&mut self, text: &str) -> Result<(), String> {
+        use arboard::{LinuxClipboardKind, SetExtLinux};
+
+        self.clipboard
+            .set()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text(text)
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn write_primary_text(&mut self, _text: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn read_hints(&mut self) -> Result<ClipboardHints, String> {
+        hints::read_system_hints()
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+enum MemoryContents {
+    #[default]
+    Empty,
+    Text(String),
+    Html { html: String, text: String },
+    Image(ImageData<'static>),
+    Files(Vec<PathBuf>),
+}
+
+/// Clipboard that lives in memory. Clones share the same contents, so a test
+/// can keep one handle to "copy" with while the pipeline owns another.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryClipboard {
+    contents: Arc<Mutex<MemoryContents>>,
+    primary: Arc<Mutex<Option<String>>>,
+    hints: Arc<Mutex<ClipboardHints>>,
+}
+
+#[cfg(test)]
+impl InMemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_text(&self, text: impl Into<String>) {
+        *self.contents.lock().unwrap() = MemoryContents::Text(text.into());
+    }
+
+    pub fn set_html(&self, html: impl Into<String>, text: impl Into<String>) {
+        *self.contents.lock().unwrap() = MemoryContents::Html {
+            html: html.into(),
+            text: text.into(),
+        };
+    }
+
+    pub fn set_image(&self, image: ImageData<'static>) {
+        *self.contents.lock().unwrap() = MemoryContents::Image(image);
+    }
+
+    pub fn set_files(&self, paths: Vec<PathBuf>) {
+        *self.contents.lock().unwrap() = MemoryContents::Files(paths);
+    }
+
+    pub fn set_primary(&self, text: impl Into<String>) {
+        *self.primary.lock().unwrap() = Some(text.into());
+    }
+
+    /// Hints reported for whatever is set next, as a password manager would.
+    pub fn set_hints(&self, hints: ClipboardHints) {
+        *self.hints.lock().unwrap() = hints;
+    }
+
+    pub fn clear(&self) {
+        *self.contents.lock().unwrap() = MemoryContents::Empty;
+    }
+}
+
+#[cfg(test)]
+impl ClipboardSource for InMemoryClipboard {
+    fn read_text(&mut self) -> Result<Option<String>, String> {
+        match &*self.contents.lock().unwrap() {
+            MemoryContents::Text(text) | MemoryContents::Html { text, .. } => {
+                Ok(Some(text.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn read_html(&mut self) -> Result<Option<String>, String> {
+        match &*self.contents.lock().unwrap() {
+            MemoryContents::Html { html, .. } => Ok(Some(html.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    fn read_image(&mut self) -> Result<Option<ImageData<'static>>, String> {
+        match &*self.contents.lock().unwrap() {
+            MemoryContents::Image(image) => Ok(Some(image.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    fn read_files(&mut self) -> Result<Option<Vec<PathBuf>>, String> {
+        match &*self.contents.lock().unwrap() {
+            MemoryContents::Files(paths) => Ok(Some(paths.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<(), String> {
+        self.set_text(text);
+        Ok(())
+    }
+
+    fn write_html(&mut self, html: &str, text: &str) -> Result<(), String> {
+        self.set_html(html, text);
+        Ok(())
+    }
+
+    fn write_image(&mut self, image: ImageData<'static>) -> Result<(), String> {
+        self.set_image(image);
+        Ok(())
+    }
+
+    fn write_files(&mut self, paths: &[PathBuf]) -> Result<(), String> {
+        self.set_files(paths.to_vec());
+        Ok(())
+    }
+
+    fn read_primary_text(&mut self) -> Result<Option<String>, String> {
+        Ok(self.primary.lock().unwrap().clone())
+    }
+
+    fn write_primary_text(&mut self, text: &str) -> Result<(), String> {
+        self.set_primary(text);
+        Ok(())
+    }
+
+    fn read_hints(&mut self) -> Result<ClipboardHints, String> {
+        Ok(*self.hints.lock().unwrap())
+    }
+}
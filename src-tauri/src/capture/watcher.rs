@@ -0,0 +1,289 @@
+// src/capture/watcher.rs
+//
+// Tells the monitor loop *when* to read the clipboard. Where the platform
+// offers change notifications (X11 XFixes, wlroots data-control on Wayland)
+// a background thread forwards them over a channel; everywhere else we fall
+// back to a fixed polling interval.
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time;
+
+/// Even with notifications we re-check now and then, so a missed event or a
+/// compositor restart can never stall capture for long.
+const EVENT_SAFETY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMethod {
+    X11Xfixes,
+    WaylandDataControl,
+    Polling,
+}
+
+pub struct ClipboardWatcher {
+    method: WatchMethod,
+    poll_interval: Duration,
+    events: Option<UnboundedReceiver<()>>,
+}
+
+impl ClipboardWatcher {
+    /// Picks the best available change source for this session.
+    pub fn start(poll_interval: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let method = start_native_watcher(tx);
+        println!("👀 Clipboard watcher using {:?}", method);
+
+        Self {
+            method,
+            poll_interval,
+            events: (method != WatchMethod::Polling).then_some(rx),
+        }
+    }
+
+    pub fn method(&self) -> WatchMethod {
+        self.method
+    }
+
+    /// Resolves when the clipboard may have changed.
+    pub async fn wait_for_change(&mut self) {
+        let Some(events) = self.events.as_mut() else {
+            time::sleep(self.poll_interval).await;
+            return;
+        };
+
+        match time::timeout(EVENT_SAFETY_INTERVAL, events.recv()).await {
+            Ok(Some(())) => {
+                // Collapse bursts of notifications into a single read
+                while events.try_recv().is_ok() {}
+            }
+            Ok(None) => {
+                eprintln!("⚠️ Clipboard watcher thread stopped, falling back to polling");
+                self.events = None;
+                self.method = WatchMethod::Polling;
+            }
+            Err(_) => {
+                // Safety re-check, nothing arrived
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn start_native_watcher(tx: UnboundedSender<()>) -> WatchMethod {
+    // Prefer the native Wayland protocol when running under a Wayland
+    // compositor; XWayland's XFixes only sees X11 clients' copies.
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match wayland::spawn(tx.clone()) {
+            Ok(()) => return WatchMethod::WaylandDataControl,
+            Err(e) => eprintln!("ℹ️ Wayland data-control unavailable: {}", e),
+        }
+    }
+
+    if std::env::var_os("DISPLAY").is_some() {
+        match x11::spawn(tx) {
+            Ok(()) => return WatchMethod::X11Xfixes,
+            Err(e) => eprintln!("ℹ️ X11 XFixes unavailable: {}", e),
+        }
+    }
+
+    WatchMethod::Polling
+}
+
+#[cfg(not(target_os = "linux"))]
+fn start_native_watcher(_tx: UnboundedSender<()>) -> WatchMethod {
+    WatchMethod::Polling
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use tokio::sync::mpsc::UnboundedSender;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, WindowClass};
+    use x11rb::protocol::Event;
+
+    /// Connects and subscribes on the calling thread so setup errors are
+    /// reported synchronously, then hands the connection to a reader thread.
+    pub fn spawn(tx: UnboundedSender<()>) -> Result<(), Box<dyn std::error::Error>> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        conn.xfixes_query_version(5, 0)?.reply()?;
+
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+
+        let clipboard = conn.intern_atom(false, b"CLIPBOARD")?.reply()?.atom;
+        conn.xfixes_select_selection_input(
+            window,
+            clipboard,
+            SelectionEventMask::SET_SELECTION_OWNER
+                | SelectionEventMask::SELECTION_WINDOW_DESTROY
+                | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+        )?;
+        conn.flush()?;
+
+        std::thread::Builder::new()
+            .name("clipboard-x11-watcher".into())
+            .spawn(move || loop {
+                match conn.wait_for_event() {
+                    Ok(Event::XfixesSelectionNotify(_)) => {
+                        if tx.send(()).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("❌ X11 clipboard watcher lost connection: {}", e);
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod wayland {
+    use tokio::sync::mpsc::UnboundedSender;
+    use wayland_client::protocol::{wl_registry, wl_seat::WlSeat};
+    use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+    use wayland_protocols_wlr::data_control::v1::client::{
+        zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+        zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+        zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    };
+
+    struct WatcherState {
+        tx: UnboundedSender<()>,
+        seat: Option<WlSeat>,
+        manager: Option<ZwlrDataControlManagerV1>,
+    }
+
+    pub fn spawn(tx: UnboundedSender<()>) -> Result<(), Box<dyn std::error::Error>> {
+        let conn = Connection::connect_to_env()?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        let mut state = WatcherState {
+            tx,
+            seat: None,
+            manager: None,
+        };
+        queue.roundtrip(&mut state)?;
+
+        let manager = state
+            .manager
+            .clone()
+            .ok_or("compositor does not support zwlr_data_control_manager_v1")?;
+        let seat = state.seat.clone().ok_or("no wl_seat advertised")?;
+        manager.get_data_device(&seat, &qh, ());
+        queue.roundtrip(&mut state)?;
+
+        std::thread::Builder::new()
+            .name("clipboard-wayland-watcher".into())
+            .spawn(move || loop {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    eprintln!("❌ Wayland clipboard watcher stopped: {}", e);
+                    break;
+                }
+                if state.tx.is_closed() {
+                    break;
+                }
+            })?;
+
+        Ok(())
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for WatcherState {
+        fn event(
+            state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global { name, interface, version } = event {
+                if interface == WlSeat::interface().name && state.seat.is_none() {
+                    state.seat = Some(registry.bind(name, version.min(1), qh, ()));
+                } else if interface == ZwlrDataControlManagerV1::interface().name {
+                    state.manager = Some(registry.bind(name, version.min(2), qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ZwlrDataControlDeviceV1, ()> for WatcherState {
+        fn event(
+            state: &mut Self,
+            _: &ZwlrDataControlDeviceV1,
+            event: zwlr_data_control_device_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            // We only need the notification; arboard reads the data itself.
+            match event {
+                zwlr_data_control_device_v1::Event::Selection { id } => {
+                    if let Some(offer) = id {
+                        offer.destroy();
+                    }
+                    let _ = state.tx.send(());
+                }
+                zwlr_data_control_device_v1::Event::PrimarySelection { id: Some(offer) } => {
+                    offer.destroy();
+                }
+                _ => {}
+            }
+        }
+
+        event_created_child!(WatcherState, ZwlrDataControlDeviceV1, [
+            zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+        ]);
+    }
+
+    impl Dispatch<WlSeat, ()> for WatcherState {
+        fn event(_: &mut Self, _: &WlSeat, _: <WlSeat as Proxy>::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+    }
+
+    impl Dispatch<ZwlrDataControlManagerV1, ()> for WatcherState {
+        fn event(
+            _: &mut Self,
+            _: &ZwlrDataControlManagerV1,
+            _: <ZwlrDataControlManagerV1 as Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+
+    impl Dispatch<ZwlrDataControlOfferV1, ()> for WatcherState {
+        fn event(
+            _: &mut Self,
+            _: &ZwlrDataControlOfferV1,
+            _: <ZwlrDataControlOfferV1 as Proxy>::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use tauri::command;
-use crate::db::schemas::{ClipboardEntry, NewClipboardEntry};
+use crate::db::schemas::{
+    ClipboardEntry, EntrySummary, HistoryCursor, HistoryFilter, HistoryOrder, HistoryPage, NewClipboardEntry,
+    PageDirection, SearchHit,
+};
 use crate::auth::verify_firebase_token;
 use crate::db::users_repository::UsersRepository;
 use crate::db::schemas::users::{NewUser, UserResponse, PurgeCadence, Plan};
@@ -12,6 +15,7 @@ use serde_json;
 use tauri::{State, Window, Manager};
 use sqlx::PgPool;
 use tauri_plugin_updater::UpdaterExt;
+use std::collections::HashMap;
 use std::time::Duration;
 use tauri::AppHandle;
 use tauri::async_runtime::{self, Mutex};
@@ -28,7 +32,19 @@ use url::Url;
 use uuid::Uuid;
 use crate::DbPools;
 use crate::db::sqlite_users_repository::SqliteUsersRepository;
+use crate::db::capture_settings_repository::CaptureSettingsRepository;
+use crate::db::sqlite_capture_settings_repository::SqliteCaptureSettingsRepository;
+use crate::db::schemas::capture_settings::CaptureSettings;
+use crate::db::capture_rules_repository::CaptureRulesRepository;
+use crate::db::blobs_repository::BlobsRepository;
+use crate::db::sqlite_blobs_repository::SqliteBlobsRepository;
+use crate::db::sqlite_capture_rules_repository::SqliteCaptureRulesRepository;
+use crate::capture::rules as capture_rules;
+use crate::db::schemas::capture_rules::{
+    CaptureRule, LocalCaptureRule, NewCaptureRule, RuleActions, RuleConditions, UpdateCaptureRule,
+};
 use sqlx::SqlitePool;
+use crate::search::fuzzy::{self, FuzzyHit};
 
 // ======================= GOOGLE LOGIN =======================
 
@@ -217,6 +233,80 @@ pub async fn get_my_entries(
     Ok(entries)
 }
 
+/// Pages through history with a cursor instead of a growing limit. Pass the
+/// page's `older` cursor to scroll back and its `newer` cursor (direction
+/// `newer`) to load copies made since; an empty page leaves the caller's
+/// cursor as it was. A re-copied entry moves to the top, so callers merge
+/// pages by id.
+#[command]
+pub async fn get_history_page(
+    order: Option<HistoryOrder>,
+    direction: Option<PageDirection>,
+    cursor: Option<HistoryCursor>,
+    filter: Option<HistoryFilter>,
+    limit: Option<i64>,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<HistoryPage, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let filter = filter.unwrap_or_default();
+    check_history_filter(&filter)?;
+
+    SqliteClipboardRepository::get_history_page(
+        &db_pools.sqlite,
+        &organization_id,
+        order.unwrap_or_default(),
+        direction.unwrap_or_default(),
+        cursor.as_ref(),
+        &filter,
+        limit,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// `get_history_page` for the list view: previews instead of full content,
+/// so even long histories load at once. Opening an entry fetches the rest
+/// with `get_full_entry`.
+#[command]
+pub async fn get_history_summaries(
+    order: Option<HistoryOrder>,
+    direction: Option<PageDirection>,
+    cursor: Option<HistoryCursor>,
+    filter: Option<HistoryFilter>,
+    limit: Option<i64>,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<HistoryPage<EntrySummary>, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let filter = filter.unwrap_or_default();
+    check_history_filter(&filter)?;
+
+    SqliteClipboardRepository::get_history_summaries(
+        &db_pools.sqlite,
+        &organization_id,
+        order.unwrap_or_default(),
+        direction.unwrap_or_default(),
+        cursor.as_ref(),
+        &filter,
+        limit,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+fn check_history_filter(filter: &HistoryFilter) -> Result<(), String> {
+    if let Some(content_type) = filter.content_type.as_deref().map(|t| t.trim().to_lowercase()) {
+        if !content_type.is_empty() && !crate::capture::classifier::known_types().any(|known| known == content_type) {
+            return Err(format!("Unknown content type '{}'", content_type));
+        }
+    }
+
+    Ok(())
+}
+
 #[command]
 pub async fn get_recent_entries(
     hours: Option<i32>,
@@ -278,25 +368,30 @@ pub async fn delete_entry(
 
     let sqlite_pool = &db_pools.sqlite;
 
-    // 1) Get server_id from SQLite for this local id
+    // 1) Get server_id (and any stored image file) from SQLite for this local id
     let row = sqlx::query(
         r#"
-        SELECT server_id
+        SELECT server_id, image_path
         FROM clipboard_entries
-        WHERE id = ?1
+        WHERE id = ?1 AND organization_id = ?2
         "#,
     )
     .bind(id)
+    .bind(&organization_id)
     .fetch_optional(sqlite_pool)
     .await
-    .map_err(|e| format!("Failed to fetch server_id from SQLite: {}", e))?;
+    .map_err(|e| format!("Failed to fetch server_id from SQLite: {}", e))?
+    .ok_or_else(|| "Clipboard entry not found".to_string())?;
 
-    let server_id_opt: Option<String> = row
-        .as_ref()
-        .and_then(|r| r.try_get::<String, _>("server_id").ok());
+    let server_id_opt: Option<String> = row.try_get::<String, _>("server_id").ok();
+
+    let image_path_opt: Option<String> = row
+        .try_get::<Option<String>, _>("image_path")
+        .ok()
+        .flatten();
 
     // 2) Delete from SQLite (local)
-    let deleted_local = SqliteClipboardRepository::delete_entry(sqlite_pool, id)
+    let deleted_local = SqliteClipboardRepository::delete_entry(sqlite_pool, id, &organization_id)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -304,6 +399,18 @@ pub async fn delete_entry(
         return Err("Local delete failed".into());
     }
 
+    if let Some(file_name) = image_path_opt {
+        // Another row (another organization, or the same image copied again)
+        // may still show the file
+        let in_use = SqliteClipboardRepository::image_in_use(sqlite_pool, &file_name)
+            .await
+            .unwrap_or(true);
+        if !in_use {
+            crate::db::image_store::delete_image(&file_name);
+        }
+    }
+    fuzzy::forget(id);
+
     // 3) Delete from Postgres (cloud) if pool + server_id available
     if let (Some(pg_pool), Some(server_id_str)) = (&db_pools.pg, server_id_opt) {
         if let Ok(server_id) = server_id_str.parse::<i64>() {
@@ -329,17 +436,114 @@ pub async fn update_entry_content(
     new_content: String,
     db_pools: tauri::State<'_, DbPools>,
 ) -> Result<ClipboardEntry, String> {
-    SqliteClipboardRepository::update_entry_content(&db_pools.sqlite, id, &new_content)
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    // Entries of other organizations are not found, before anything is stored for them
+    SqliteClipboardRepository::get_by_id_for_org(&db_pools.sqlite, id, &organization_id)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Clipboard entry not found".to_string())?;
+
+    let settings = SqliteCaptureSettingsRepository::get_for_org(&db_pools.sqlite, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to load capture settings: {}", e))?;
+
+    // Edited text over the inline limit goes to the blob store like a copy
+    let blob_ref = if new_content.len() as u64 > settings.max_inline_bytes {
+        let blob = crate::db::blob_store::save_blob(&new_content)
+            .map_err(|e| format!("Failed to store large content: {}", e))?;
+        SqliteBlobsRepository::record(&db_pools.sqlite, &organization_id, &blob)
+            .await
+            .map_err(|e| format!("Failed to record blob: {}", e))?;
+        Some(blob.hash)
+    } else {
+        None
+    };
+
+    let updated = SqliteClipboardRepository::update_entry_content(&db_pools.sqlite, id, &organization_id, &new_content, blob_ref.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    fuzzy::record(&db_pools.sqlite, &updated).await;
+    Ok(updated)
 }
 
+/// Searches history with the search box syntax (`tag:work app:firefox
+/// "exact phrase" ...`, see `search::query`).
 #[command]
 pub async fn search_entries(
     query: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<Vec<SearchHit>, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let query = crate::search::query::parse(&query).map_err(|e| e.to_string())?;
+
+    SqliteClipboardRepository::search(&db_pools.sqlite, &organization_id, &query, limit, offset)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Typo-tolerant search over recent history for half-remembered snippets.
+/// Each hit carries the char offsets in `content` that matched.
+#[command]
+pub async fn fuzzy_search_entries(
+    query: String,
+    limit: Option<i64>,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<Vec<FuzzyHit>, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    fuzzy::warm(&db_pools.sqlite, &organization_id).await?;
+    let limit = limit.unwrap_or(50).clamp(1, 200) as usize;
+
+    let ids = fuzzy::search(&organization_id, &query, limit);
+    let mut entries: HashMap<i64, ClipboardEntry> =
+        SqliteClipboardRepository::get_by_ids(&db_pools.sqlite, &organization_id, &ids)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|entry| (entry.id, entry))
+            .collect();
+
+    let mut hits = Vec::new();
+    for id in ids {
+        let Some(entry) = entries.remove(&id).filter(fuzzy::is_searchable) else {
+            // Deleted or marked sensitive since it was indexed
+            fuzzy::forget(id);
+            continue;
+        };
+
+        // Score against the stored text in case it was edited since
+        if let Some(matched) = fuzzy::match_text(&query, &entry.content) {
+            hits.push(FuzzyHit {
+                entry,
+                score: matched.score,
+                positions: matched.positions,
+            });
+        }
+    }
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    Ok(hits)
+}
+
+/// Entries the classifier tagged with `content_type` ("json", "code", "color", ...).
+#[command]
+pub async fn get_entries_by_type(
+    content_type: String,
+    limit: Option<i64>,
     db_pools: tauri::State<'_, DbPools>,
 ) -> Result<Vec<ClipboardEntry>, String> {
-    SqliteClipboardRepository::search_content(&db_pools.sqlite, &query)
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteClipboardRepository::get_by_content_type(&db_pools.sqlite, &organization_id, &content_type, limit)
         .await
         .map_err(|e| e.to_string())
 }
@@ -352,11 +556,16 @@ pub async fn update_entry(
 ) -> Result<ClipboardEntry, String> {
     use crate::db::schemas::UpdateClipboardEntry;
 
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
     let update_struct = UpdateClipboardEntry {
         is_pinned: updates.get("is_pinned").and_then(|v| v.as_bool()),
         tags: updates.get("tags").and_then(|v| {
             if v.is_string() {
-                Some(v.as_str().unwrap().to_string())
+                // Accept the loose formats older builds sent, store a JSON array
+                let names = crate::db::database::json_to_tags(&v.as_str().map(str::to_string));
+                serde_json::to_string(&names).ok()
             } else if v.is_array() {
                 serde_json::to_string(v).ok()
             } else {
@@ -367,7 +576,7 @@ pub async fn update_entry(
     };
 
     // 🔁 Update in SQLite, mark sync_status='local' inside this fn
-    SqliteClipboardRepository::update_entry(&db_pools.sqlite, id, update_struct)
+    SqliteClipboardRepository::update_entry(&db_pools.sqlite, id, &organization_id, update_struct)
         .await
         .map_err(|e| e.to_string())
 }
@@ -1131,6 +1340,26 @@ pub async fn update_tag(
         .map_err(|e: sqlx::Error| format!("Failed to update tag: {}", e))?
         .ok_or_else(|| "Tag not found".to_string())?;
 
+    // Mirror into the local copy so entries carrying the tag show the new
+    // name right away, not after the next bootstrap
+    if let Err(e) = sqlx::query(
+        r#"
+        UPDATE tags
+        SET name = ?1, color = ?2, updated_at = ?3, sync_status = 'synced'
+        WHERE organization_id = ?4 AND server_id = ?5
+        "#,
+    )
+    .bind(&updated_tag.name)
+    .bind(&updated_tag.color)
+    .bind(updated_tag.updated_at)
+    .bind(&organization_id)
+    .bind(updated_tag.id)
+    .execute(&db_pools.sqlite)
+    .await
+    {
+        eprintln!("⚠️ Failed to mirror tag {} update into SQLite: {}", updated_tag.id, e);
+    }
+
     println!(
         "✅ Tag updated successfully - ID: {}, Name: {}",
         updated_tag.id, updated_tag.name
@@ -1383,7 +1612,10 @@ pub async fn assign_tag_to_entry(
 ) -> Result<ClipboardEntry, String> {
     println!("🟢 Assigning tag '{}' to entry {}", tag_name, clipboard_entry_id);
 
-    SqliteClipboardRepository::assign_tag(&db_pools.sqlite, clipboard_entry_id, &tag_name).await
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteClipboardRepository::assign_tag(&db_pools.sqlite, clipboard_entry_id, &organization_id, &tag_name).await
 }
 
 #[tauri::command]
@@ -1394,7 +1626,25 @@ pub async fn remove_tag_from_entry(
 ) -> Result<ClipboardEntry, String> {
     println!("🔴 Removing tag '{}' from entry {}", tag_name, clipboard_entry_id);
 
-    SqliteClipboardRepository::remove_tag(&db_pools.sqlite, clipboard_entry_id, &tag_name).await
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteClipboardRepository::remove_tag(&db_pools.sqlite, clipboard_entry_id, &organization_id, &tag_name).await
+}
+
+/// Entries carrying a local tag (ids as returned by `get_tags`), newest first.
+#[tauri::command]
+pub async fn get_entries_by_tag(
+    tag_id: i64,
+    limit: Option<i64>,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<Vec<ClipboardEntry>, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteClipboardRepository::get_by_tag(&db_pools.sqlite, &organization_id, tag_id, limit)
+        .await
+        .map_err(|e| format!("Failed to fetch entries for tag {}: {}", tag_id, e))
 }
 
 // ======================= PURGE / AUTO PURGE =======================
@@ -1932,6 +2182,203 @@ pub async fn get_current_user_retain_tags(
     }
 }
 
+// ======================= CAPTURE SETTINGS =======================
+
+#[tauri::command]
+pub async fn get_capture_settings(
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<CaptureSettings, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteCaptureSettingsRepository::get_for_org(&db_pools.sqlite, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to load capture settings: {}", e))
+}
+
+#[tauri::command]
+pub async fn update_capture_settings(
+    settings: CaptureSettings,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<CaptureSettings, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let settings = settings.normalized();
+
+    println!(
+        "🔄 Updating capture settings for org {} - {} excluded, {} allowed apps",
+        organization_id,
+        settings.excluded_apps.len(),
+        settings.allowed_apps.len()
+    );
+
+    // 1) LOCAL FIRST: update in SQLite
+    let sqlite_pool = &db_pools.sqlite;
+    SqliteCaptureSettingsRepository::save_for_org(sqlite_pool, &organization_id, &settings)
+        .await
+        .map_err(|e| format!("Failed to update capture settings in local DB: {}", e))?;
+
+    println!("✅ [SQLite] Capture settings updated locally");
+
+    // 2) BEST-EFFORT SYNC TO POSTGRES
+    if let Some(pg_pool) = db_pools.pg.as_ref() {
+        match CaptureSettingsRepository::upsert_for_org(pg_pool, &organization_id, &settings).await {
+            Ok(()) => {
+                if let Err(e) =
+                    SqliteCaptureSettingsRepository::mark_as_synced(sqlite_pool, &organization_id).await
+                {
+                    eprintln!("⚠️ Failed to mark capture settings as synced: {}", e);
+                }
+                println!("✅ [Postgres] Capture settings synced for org {}", organization_id);
+            }
+            Err(e) => {
+                eprintln!("⚠️ Failed to sync capture settings to Postgres: {}", e);
+            }
+        }
+    } else {
+        eprintln!("ℹ️ Postgres pool not available, capture settings will be synced later");
+    }
+
+    Ok(settings)
+}
+
+// ======================= CAPTURE RULES =======================
+
+#[tauri::command]
+pub async fn get_capture_rules(
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<Vec<CaptureRule>, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    SqliteCaptureRulesRepository::get_for_org(&db_pools.sqlite, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to load capture rules: {}", e))
+}
+
+#[tauri::command]
+pub async fn create_capture_rule(
+    rule: NewCaptureRule,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<CaptureRule, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let rule = NewCaptureRule {
+        conditions: rule.conditions.normalized(),
+        actions: rule.actions.normalized(),
+        ..rule
+    };
+    CaptureRule::validate(&rule.name, &rule.conditions, &rule.actions)?;
+
+    println!("📏 Creating capture rule '{}' for org {}", rule.name.trim(), organization_id);
+
+    let local = SqliteCaptureRulesRepository::create(&db_pools.sqlite, &organization_id, &rule)
+        .await
+        .map_err(|e| format!("Failed to create capture rule: {}", e))?;
+    capture_rules::invalidate(&organization_id);
+
+    push_capture_rule(db_pools.pg.as_ref(), &db_pools.sqlite, &local).await;
+
+    Ok(local.parsed())
+}
+
+#[tauri::command]
+pub async fn update_capture_rule(
+    id: i64,
+    updates: UpdateCaptureRule,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<CaptureRule, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let current = SqliteCaptureRulesRepository::get_row(&db_pools.sqlite, id, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to load capture rule: {}", e))?
+        .ok_or_else(|| "Capture rule not found".to_string())?
+        .parsed();
+
+    let updates = UpdateCaptureRule {
+        conditions: updates.conditions.map(RuleConditions::normalized),
+        actions: updates.actions.map(RuleActions::normalized),
+        ..updates
+    };
+    CaptureRule::validate(
+        updates.name.as_deref().unwrap_or(&current.name),
+        updates.conditions.as_ref().unwrap_or(&current.conditions),
+        updates.actions.as_ref().unwrap_or(&current.actions),
+    )?;
+
+    let local = SqliteCaptureRulesRepository::update(&db_pools.sqlite, id, &organization_id, &updates)
+        .await
+        .map_err(|e| format!("Failed to update capture rule: {}", e))?
+        .ok_or_else(|| "Capture rule not found".to_string())?;
+    capture_rules::invalidate(&organization_id);
+
+    push_capture_rule(db_pools.pg.as_ref(), &db_pools.sqlite, &local).await;
+
+    Ok(local.parsed())
+}
+
+#[tauri::command]
+pub async fn delete_capture_rule(
+    id: i64,
+    db_pools: tauri::State<'_, DbPools>,
+) -> Result<bool, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
+
+    let server_id = SqliteCaptureRulesRepository::get_row(&db_pools.sqlite, id, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to load capture rule: {}", e))?
+        .and_then(|row| row.server_id);
+
+    let deleted = SqliteCaptureRulesRepository::delete(&db_pools.sqlite, id, &organization_id)
+        .await
+        .map_err(|e| format!("Failed to delete capture rule: {}", e))?;
+    capture_rules::invalidate(&organization_id);
+
+    // Best effort; otherwise the tombstone is cleared by the next sync
+    if let (Some(pg_pool), Some(server_id)) = (db_pools.pg.as_ref(), server_id) {
+        match CaptureRulesRepository::delete(pg_pool, server_id, &organization_id).await {
+            Ok(()) => {
+                if let Err(e) = SqliteCaptureRulesRepository::purge(&db_pools.sqlite, id).await {
+                    eprintln!("⚠️ Failed to remove deleted capture rule {}: {}", id, e);
+                }
+                println!("✅ [Postgres] Capture rule {} deleted", server_id);
+            }
+            Err(e) => eprintln!("⚠️ Failed to delete capture rule from Postgres: {}", e),
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Best-effort push of one rule right after a local change.
+async fn push_capture_rule(
+    pg_pool: Option<&PgPool>,
+    sqlite_pool: &SqlitePool,
+    local: &LocalCaptureRule,
+) {
+    let Some(pg_pool) = pg_pool else {
+        eprintln!("ℹ️ Postgres pool not available, capture rule will be synced later");
+        return;
+    };
+
+    match CaptureRulesRepository::upsert_from_local(pg_pool, local).await {
+        Ok(server_id) => {
+            if let Err(e) =
+                SqliteCaptureRulesRepository::mark_as_synced(sqlite_pool, local.id, server_id).await
+            {
+                eprintln!("⚠️ Failed to mark capture rule as synced: {}", e);
+            }
+            println!("✅ [Postgres] Capture rule '{}' synced", local.name);
+        }
+        Err(e) => eprintln!("⚠️ Failed to sync capture rule to Postgres: {}", e),
+    }
+}
+
 // ======================= SYNC & BOOTSTRAP (OFFLINE) =======================
 
 #[tauri::command]
@@ -1941,6 +2388,9 @@ pub async fn sync_clipboard_to_cloud(
     sync_clipboard_to_cloud_internal(&db_pools).await
 }
 
+/// Large-content blobs uploaded per sync run.
+const BLOB_UPLOAD_BATCH: i64 = 8;
+
 pub async fn sync_clipboard_to_cloud_internal(
     db_pools: &DbPools,
 ) -> Result<usize, String> {
@@ -1957,122 +2407,134 @@ pub async fn sync_clipboard_to_cloud_internal(
     println!("🔄 Starting sync from SQLite → Neon for org: {}", organization_id);
 
     // ======================================================
-    // 1) CLIPBOARD ENTRIES
+    // 1) TAGS, before the entries that link to them by name
     // ======================================================
-    let pending_entries = SqliteClipboardRepository::get_pending_sync_entries_for_org(
-        sqlite_pool,
-        &organization_id,
-        Some(500), // batch size
-    )
-    .await
-    .map_err(|e| format!("Failed to fetch pending entries from SQLite: {}", e))?;
+    let sqlite_tag_repo = SqliteTagRepository::new(sqlite_pool.clone());
+    let pg_tag_repo = TagRepository::new(pg_pool.clone());
 
-    if pending_entries.is_empty() {
+    let pending_tags = sqlite_tag_repo
+        .get_pending_sync_tags_for_org(&organization_id, Some(500))
+        .await
+        .map_err(|e| format!("Failed to fetch pending tags from SQLite: {}", e))?;
+
+    if pending_tags.is_empty() {
         println!(
-            "ℹ️ No pending clipboard entries to sync for org {}",
+            "ℹ️ No pending tags to sync for org {}",
             organization_id
         );
     } else {
-        println!(
-            "📦 Found {} pending clipboard entries to sync",
-            pending_entries.len()
-        );
+        println!("🏷️ Found {} pending tags to sync", pending_tags.len());
     }
 
-    let mut synced_entries = 0usize;
+    let mut synced_tags = 0usize;
 
-    for local in pending_entries {
-        let new_entry = crate::db::schemas::NewClipboardEntry {
-            content: local.content.clone(),
-            content_type: local.content_type.clone(),
-            content_hash: local.content_hash.clone(),
-            source_app: local.source_app.clone(),
-            source_window: local.source_window.clone(),
-            timestamp: local.timestamp,
-            tags: local.tags.clone(),
-            is_pinned: local.is_pinned,
-            organization_id: local.organization_id.clone(),
+    for local_tag in pending_tags {
+        let new_tag = crate::db::schemas::tags::NewTag {
+            organization_id: local_tag.organization_id.clone(),
+            name: local_tag.name.clone(),
+            color: local_tag.color.clone(),
         };
 
-        let save_result = ClipboardRepository::save_entry(pg_pool, new_entry)
-            .await
-            .map_err(|e| e.to_string());
+        let save_result = pg_tag_repo.upsert_by_name(&new_tag).await;
 
         match save_result {
-            Ok(cloud_entry) => {
-                if let Err(e) = SqliteClipboardRepository::mark_as_synced(
-                    sqlite_pool,
-                    local.id,
-                    cloud_entry.id,
-                )
-                .await
+            Ok(cloud_tag) => {
+                if let Err(e) = sqlite_tag_repo
+                    .mark_as_synced(local_tag.id, cloud_tag.id)
+                    .await
                 {
                     eprintln!(
-                        "⚠️ Failed to mark local entry {} as synced: {}",
-                        local.id, e
+                        "⚠️ Failed to mark local tag {} as synced: {}",
+                        local_tag.id, e
                     );
                 } else {
-                    synced_entries += 1;
+                    synced_tags += 1;
                 }
             }
-            Err(err_msg) => {
+            Err(e) => {
                 eprintln!(
-                    "❌ Failed to sync local entry {} to Neon: {}",
-                    local.id, err_msg
+                    "❌ Failed to sync local tag {} to Postgres: {}",
+                    local_tag.id, e
                 );
             }
         }
     }
 
     // ======================================================
-    // 2) TAGS
+    // 2) CLIPBOARD ENTRIES
     // ======================================================
-    let sqlite_tag_repo = SqliteTagRepository::new(sqlite_pool.clone());
-    let pg_tag_repo = TagRepository::new(pg_pool.clone());
-
-    let pending_tags = sqlite_tag_repo
-        .get_pending_sync_tags_for_org(&organization_id, Some(500))
-        .await
-        .map_err(|e| format!("Failed to fetch pending tags from SQLite: {}", e))?;
+    let pending_entries = SqliteClipboardRepository::get_pending_sync_entries_for_org(
+        sqlite_pool,
+        &organization_id,
+        Some(500), // batch size
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch pending entries from SQLite: {}", e))?;
 
-    if pending_tags.is_empty() {
+    if pending_entries.is_empty() {
         println!(
-            "ℹ️ No pending tags to sync for org {}",
+            "ℹ️ No pending clipboard entries to sync for org {}",
             organization_id
         );
     } else {
-        println!("🏷️ Found {} pending tags to sync", pending_tags.len());
+        println!(
+            "📦 Found {} pending clipboard entries to sync",
+            pending_entries.len()
+        );
     }
 
-    let mut synced_tags = 0usize;
+    let mut synced_entries = 0usize;
 
-    for local_tag in pending_tags {
-        let new_tag = crate::db::schemas::tags::NewTag {
-            organization_id: local_tag.organization_id.clone(),
-            name: local_tag.name.clone(),
-            color: local_tag.color.clone(),
+    for local in pending_entries {
+        let new_entry = crate::db::schemas::NewClipboardEntry {
+            content: local.content.clone(),
+            content_type: local.content_type.clone(),
+            content_hash: local.content_hash.clone(),
+            source_app: local.source_app.clone(),
+            source_window: local.source_window.clone(),
+            timestamp: local.timestamp,
+            tags: local.tags.clone(),
+            is_pinned: local.is_pinned,
+            organization_id: local.organization_id.clone(),
+            image_width: local.image_width,
+            image_height: local.image_height,
+            byte_size: local.byte_size,
+            image_path: None, // image files stay on this machine
+            is_sensitive: false, // sensitive rows are never selected for sync
+            expires_at: None,
+            copy_count: local.copy_count.max(1),
+            last_source_app: local.last_source_app.clone(),
+            content_html: local.content_html.clone(),
+            file_list: None, // paths only mean something on this machine
+            content_meta: local.content_meta.clone(),
+            blob_ref: local.blob_ref.clone(), // the blob itself is uploaded separately
         };
 
-        let save_result = pg_tag_repo.create_tag(&new_tag).await;
+        let save_result = ClipboardRepository::save_entry(pg_pool, new_entry)
+            .await
+            .map_err(|e| e.to_string());
 
         match save_result {
-            Ok(cloud_tag) => {
-                if let Err(e) = sqlite_tag_repo
-                    .mark_as_synced(local_tag.id, cloud_tag.id)
-                    .await
+            Ok(cloud_entry) => {
+                if let Err(e) = SqliteClipboardRepository::mark_as_synced(
+                    sqlite_pool,
+                    local.id,
+                    cloud_entry.id,
+                )
+                .await
                 {
                     eprintln!(
-                        "⚠️ Failed to mark local tag {} as synced: {}",
-                        local_tag.id, e
+                        "⚠️ Failed to mark local entry {} as synced: {}",
+                        local.id, e
                     );
                 } else {
-                    synced_tags += 1;
+                    synced_entries += 1;
                 }
             }
-            Err(e) => {
+            Err(err_msg) => {
                 eprintln!(
-                    "❌ Failed to sync local tag {} to Postgres: {}",
-                    local_tag.id, e
+                    "❌ Failed to sync local entry {} to Neon: {}",
+                    local.id, err_msg
                 );
             }
         }
@@ -2158,12 +2620,109 @@ pub async fn sync_clipboard_to_cloud_internal(
         );
     }
 
+    // Capture settings (app deny/allow lists) ride along with user settings
+    let local_capture_settings =
+        SqliteCaptureSettingsRepository::get_row(sqlite_pool, &organization_id)
+            .await
+            .map_err(|e| format!("Failed to fetch local capture settings: {}", e))?;
+
+    if let Some(local) = local_capture_settings.filter(|row| row.sync_status != "synced") {
+        CaptureSettingsRepository::upsert_for_org(pg_pool, &organization_id, &local.parsed())
+            .await
+            .map_err(|e| format!("Failed to update cloud capture settings: {}", e))?;
+
+        SqliteCaptureSettingsRepository::mark_as_synced(sqlite_pool, &organization_id)
+            .await
+            .map_err(|e| format!("Failed to mark capture settings as synced: {}", e))?;
+
+        synced_user_settings += 1;
+        println!("✅ Synced capture settings for org {}", organization_id);
+    }
+
+    // ======================================================
+    // 4) CAPTURE RULES
+    // ======================================================
+    let pending_rules =
+        SqliteCaptureRulesRepository::get_pending_sync_for_org(sqlite_pool, &organization_id)
+            .await
+            .map_err(|e| format!("Failed to fetch pending capture rules from SQLite: {}", e))?;
+
+    let mut synced_rules = 0usize;
+
+    for local_rule in pending_rules {
+        // Some(server_id) once pushed, None once a deleted rule is gone from the cloud
+        let result = if local_rule.sync_status == "deleted" {
+            match local_rule.server_id {
+                Some(server_id) => CaptureRulesRepository::delete(pg_pool, server_id, &organization_id)
+                    .await
+                    .map(|()| None),
+                None => Ok(None),
+            }
+        } else {
+            CaptureRulesRepository::upsert_from_local(pg_pool, &local_rule)
+                .await
+                .map(Some)
+        };
+
+        let marked = match result {
+            Ok(Some(server_id)) => {
+                SqliteCaptureRulesRepository::mark_as_synced(sqlite_pool, local_rule.id, server_id).await
+            }
+            Ok(None) => SqliteCaptureRulesRepository::purge(sqlite_pool, local_rule.id).await,
+            Err(e) => {
+                eprintln!("❌ Failed to sync capture rule {} to Postgres: {}", local_rule.id, e);
+                continue;
+            }
+        };
+
+        match marked {
+            Ok(()) => synced_rules += 1,
+            Err(e) => eprintln!("⚠️ Failed to mark capture rule {} as synced: {}", local_rule.id, e),
+        }
+    }
+
+    // ======================================================
+    // 5) LARGE CONTENT BLOBS
+    // ======================================================
+    // Rows above only carry a preview; the full text follows a few blobs
+    // per run so one huge copy doesn't hold up the rest of the sync.
+    let pending_blobs =
+        SqliteBlobsRepository::get_pending_upload(sqlite_pool, &organization_id, BLOB_UPLOAD_BATCH)
+            .await
+            .map_err(|e| format!("Failed to fetch pending blobs from SQLite: {}", e))?;
+
+    let mut synced_blobs = 0usize;
+
+    for hash in pending_blobs {
+        let data = match crate::db::blob_store::read_compressed(&hash) {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("⚠️ Skipping blob upload: {}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = BlobsRepository::upload(pg_pool, &organization_id, &hash, &data).await {
+            eprintln!("❌ Failed to upload blob {} to Postgres: {}", hash, e);
+            continue;
+        }
+
+        match SqliteBlobsRepository::mark_uploaded(sqlite_pool, &organization_id, &hash).await {
+            Ok(()) => synced_blobs += 1,
+            Err(e) => eprintln!("⚠️ Failed to mark blob {} as uploaded: {}", hash, e),
+        }
+    }
+
+    if synced_blobs > 0 {
+        println!("🗜️ Uploaded {} blobs", synced_blobs);
+    }
+
     println!(
-        "✅ Sync completed → {} clipboard entries + {} tags + {} user settings",
-        synced_entries, synced_tags, synced_user_settings
+        "✅ Sync completed → {} clipboard entries + {} tags + {} user settings + {} capture rules",
+        synced_entries, synced_tags, synced_user_settings, synced_rules
     );
 
-    Ok(synced_entries + synced_tags + synced_user_settings)
+    Ok(synced_entries + synced_tags + synced_user_settings + synced_rules)
 }
 
 #[tauri::command]
@@ -2196,64 +2755,7 @@ async fn bootstrap_from_cloud_for_org(
     println!("☁️ Bootstrapping clipboard from cloud for org: {}", organization_id);
 
     // ======================================================
-    // 1) CLIPBOARD ENTRIES (Postgres → SQLite)
-    // ======================================================
-    let remote_entries: Vec<ClipboardEntry> = ClipboardRepository::get_by_organization(
-        pg_pool,
-        organization_id,
-        None,
-    )
-    .await
-    .map_err(|e| format!("Failed to fetch remote entries from Postgres: {}", e))?;
-
-    println!(
-        "☁️ Got {} remote clipboard entries for org {}",
-        remote_entries.len(),
-        organization_id
-    );
-
-    let mut changed_entries = 0usize;
-
-    for remote in remote_entries {
-        // 1) Do we already have this Postgres row locally?
-        let local_opt = SqliteClipboardRepository::get_by_server_id(sqlite_pool, remote.id)
-            .await
-            .map_err(|e| format!("Failed to check local by server_id: {}", e))?;
-
-        if let Some(local) = local_opt {
-            // 2) UPDATE existing local row with latest remote content
-            if let Err(e) =
-                SqliteClipboardRepository::update_from_remote(sqlite_pool, local.id, &remote).await
-            {
-                eprintln!(
-                    "❌ Failed to update local entry {} from remote {}: {}",
-                    local.id, remote.id, e
-                );
-                continue;
-            }
-        } else {
-            // 3) INSERT new local row for this remote entry
-            if let Err(e) =
-                SqliteClipboardRepository::insert_from_remote(sqlite_pool, &remote).await
-            {
-                eprintln!(
-                    "❌ Failed to insert remote entry {} into SQLite: {}",
-                    remote.id, e
-                );
-                continue;
-            }
-        }
-
-        changed_entries += 1;
-    }
-
-    println!(
-        "✅ Bootstrapped/updated {} clipboard entries from cloud → local for org {}",
-        changed_entries, organization_id
-    );
-
-    // ======================================================
-    // 2) TAGS (Postgres → SQLite)
+    // 1) TAGS (Postgres → SQLite), before the entries that link to them
     // ======================================================
     println!("☁️ Bootstrapping tags from cloud for org: {}", organization_id);
 
@@ -2326,7 +2828,8 @@ async fn bootstrap_from_cloud_for_org(
                 continue;
             }
         } else {
-            // INSERT new local tag row mapped to this cloud tag
+            // INSERT new local tag row mapped to this cloud tag, or adopt a
+            // local tag with the same name
             if let Err(e) = sqlx::query(
                 r#"
                 INSERT INTO tags (
@@ -2339,6 +2842,10 @@ async fn bootstrap_from_cloud_for_org(
                     server_id
                 )
                 VALUES (?1, ?2, ?3, ?4, ?5, 'synced', ?6)
+                ON CONFLICT(organization_id, name) DO UPDATE SET
+                    color = excluded.color,
+                    sync_status = 'synced',
+                    server_id = excluded.server_id
                 "#,
             )
             .bind(&remote_tag.organization_id)
@@ -2366,12 +2873,140 @@ async fn bootstrap_from_cloud_for_org(
         changed_tags, organization_id
     );
 
+    // ======================================================
+    // 2) CLIPBOARD ENTRIES (Postgres → SQLite)
+    // ======================================================
+    let remote_entries: Vec<ClipboardEntry> = ClipboardRepository::get_by_organization(
+        pg_pool,
+        organization_id,
+        None,
+    )
+    .await
+    .map_err(|e| format!("Failed to fetch remote entries from Postgres: {}", e))?;
+
+    println!(
+        "☁️ Got {} remote clipboard entries for org {}",
+        remote_entries.len(),
+        organization_id
+    );
+
+    let mut changed_entries = 0usize;
+
+    for remote in remote_entries {
+        // 1) Do we already have this Postgres row locally?
+        let local_opt = SqliteClipboardRepository::get_by_server_id(sqlite_pool, remote.id)
+            .await
+            .map_err(|e| format!("Failed to check local by server_id: {}", e))?;
+
+        if let Some(local) = local_opt {
+            // 2) UPDATE existing local row with latest remote content
+            if let Err(e) =
+                SqliteClipboardRepository::update_from_remote(sqlite_pool, local.id, &remote).await
+            {
+                eprintln!(
+                    "❌ Failed to update local entry {} from remote {}: {}",
+                    local.id, remote.id, e
+                );
+                continue;
+            }
+        } else {
+            // 3) INSERT new local row for this remote entry
+            if let Err(e) =
+                SqliteClipboardRepository::insert_from_remote(sqlite_pool, &remote).await
+            {
+                eprintln!(
+                    "❌ Failed to insert remote entry {} into SQLite: {}",
+                    remote.id, e
+                );
+                continue;
+            }
+        }
+
+        changed_entries += 1;
+    }
+
+    println!(
+        "✅ Bootstrapped/updated {} clipboard entries from cloud → local for org {}",
+        changed_entries, organization_id
+    );
+
+    // ======================================================
+    // 3) CAPTURE SETTINGS (Postgres → SQLite)
+    // ======================================================
+    let mut changed_settings = 0usize;
+
+    let remote_settings = CaptureSettingsRepository::get_for_org(pg_pool, organization_id)
+        .await
+        .map_err(|e| format!("Failed to fetch remote capture settings: {}", e))?;
+
+    if let Some((settings, updated_at)) = remote_settings {
+        let local = SqliteCaptureSettingsRepository::get_row(sqlite_pool, organization_id)
+            .await
+            .map_err(|e| format!("Failed to fetch local capture settings: {}", e))?;
+
+        // Unsynced local edits win; they are pushed on the next sync
+        let has_local_changes = local.is_some_and(|row| row.sync_status != "synced");
+
+        if !has_local_changes {
+            SqliteCaptureSettingsRepository::replace_from_remote(
+                sqlite_pool,
+                organization_id,
+                &settings,
+                updated_at,
+            )
+            .await
+            .map_err(|e| format!("Failed to store capture settings locally: {}", e))?;
+
+            changed_settings = 1;
+        }
+    }
+
+    // ======================================================
+    // 4) CAPTURE RULES (Postgres → SQLite)
+    // ======================================================
+    let remote_rules = CaptureRulesRepository::get_for_org(pg_pool, organization_id)
+        .await
+        .map_err(|e| format!("Failed to fetch remote capture rules: {}", e))?;
+
+    let mut changed_rules = 0usize;
+
+    for remote_rule in &remote_rules {
+        let local = SqliteCaptureRulesRepository::get_by_server_id(sqlite_pool, remote_rule.id, organization_id)
+            .await
+            .map_err(|e| format!("Failed to check local capture rule by server_id: {}", e))?;
+
+        // Unsynced local edits and deletes win; they are pushed on the next sync
+        if local.as_ref().is_some_and(|row| row.sync_status != "synced") {
+            continue;
+        }
+
+        if let Err(e) = SqliteCaptureRulesRepository::upsert_from_remote(sqlite_pool, remote_rule).await {
+            eprintln!("❌ Failed to store remote capture rule {}: {}", remote_rule.id, e);
+            continue;
+        }
+
+        changed_rules += 1;
+    }
+
+    let remote_ids: Vec<i64> = remote_rules.iter().map(|rule| rule.id).collect();
+    match SqliteCaptureRulesRepository::delete_synced_missing_from(sqlite_pool, organization_id, &remote_ids).await {
+        Ok(0) => {}
+        Ok(removed) => {
+            println!("🧹 Removed {} capture rules deleted on another device", removed);
+            changed_rules += removed as usize;
+        }
+        Err(e) => eprintln!("⚠️ Failed to remove stale capture rules: {}", e),
+    }
+    if changed_rules > 0 {
+        capture_rules::invalidate(organization_id);
+    }
+
     println!(
-        "✅ Full bootstrap completed → {} clipboard entries + {} tags for org {}",
-        changed_entries, changed_tags, organization_id
+        "✅ Full bootstrap completed → {} clipboard entries + {} tags + {} settings + {} capture rules for org {}",
+        changed_entries, changed_tags, changed_settings, changed_rules, organization_id
     );
 
-    Ok(changed_entries + changed_tags)
+    Ok(changed_entries + changed_tags + changed_settings + changed_rules)
 }
 
 // ======================= PAYMENT INTEGRATION =======================
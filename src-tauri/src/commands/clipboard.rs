@@ -1,26 +1,38 @@
 // src-tauri/src/clipboard.rs
-use arboard::Clipboard;
-use tokio::time;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tauri::AppHandle;
 use tauri::Emitter;
-use serde::Serialize;
+use tauri::Manager;
+use tauri::State;
 use sqlx::{PgPool, SqlitePool};
 
-use crate::db::ClipboardRepository;                      // Postgres repo
 use crate::db::sqlite_database::SqliteClipboardRepository; // SQLite repo
-use crate::db::schemas::NewClipboardEntry;               // Shared schema
+use crate::db::blob_store;
+use crate::db::blobs_repository::BlobsRepository;
+use crate::db::image_store;
+use crate::db::schemas::ClipboardEntry;
+use crate::db::schemas::clipboard::FileRef;
+use crate::capture::control::{self, CaptureStatus};
+use crate::capture::foreground::{self, WindowDetection};
+use crate::capture::paste::{simulate_paste, ClipboardWriter, PasteContent};
+use crate::capture::pipeline::CapturePipeline;
+use crate::capture::primary::PRIMARY_POLL_INTERVAL;
+use crate::capture::settle::SettleTimer;
+use crate::capture::source::SystemClipboard;
+use crate::capture::watcher::ClipboardWatcher;
+use crate::DbPools;
 
 // Configuration
+// Only used when no change notifications are available (see capture::watcher)
 const POLL_INTERVAL_MS: u64 = 1000;
 
-#[derive(Clone, Serialize)]
-pub struct ClipboardContent {
-    pub text: String,
-    pub timestamp: u64,
-    pub content_type: String,
-    pub source_app: String,
-    pub source_window: String,
+/// What woke the monitor loop.
+enum Wake {
+    Clipboard,
+    /// A burst of clipboard changes has ended
+    Settled,
+    Primary,
 }
 
 #[cfg(target_os = "windows")]
@@ -74,7 +86,12 @@ fn extract_app_name_from_title(title: &str) -> String {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "linux")]
+pub fn get_foreground_window_info() -> Option<(String, String)> {
+    foreground::active_window()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
 pub fn get_foreground_window_info() -> Option<(String, String)> {
     None
 }
@@ -84,106 +101,291 @@ pub async fn start_clipboard_monitoring(
     pg_pool: Option<PgPool>,
     sqlite_pool: SqlitePool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut clipboard = Clipboard::new()?;
-    let mut last_content = String::new();
+    let source = SystemClipboard::new()?;
+    let mut pipeline = CapturePipeline::new(source, sqlite_pool, pg_pool);
+    let mut watcher = ClipboardWatcher::start(Duration::from_millis(POLL_INTERVAL_MS));
+
+    let (writer, mut write_requests) = ClipboardWriter::channel();
+    app_handle.manage(writer);
+
+    // PRIMARY has no usable change notification, see capture::primary
+    let watch_primary = cfg!(target_os = "linux");
+    let mut primary_poll = tokio::time::interval(PRIMARY_POLL_INTERVAL);
+    primary_poll.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut settle = SettleTimer::default();
 
-    println!("🔍 Clipboard monitoring started with window detection...");
+    println!(
+        "🔍 Clipboard monitoring started ({:?}, window detection {:?})...",
+        watcher.method(),
+        foreground::detection_method()
+    );
 
     loop {
-        time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        let wake = tokio::select! {
+            _ = watcher.wait_for_change() => Wake::Clipboard,
+            _ = tokio::time::sleep_until(settle.deadline().unwrap_or_else(tokio::time::Instant::now)),
+                if settle.deadline().is_some() => Wake::Settled,
+            _ = primary_poll.tick(), if watch_primary => Wake::Primary,
+            Some(request) = write_requests.recv() => {
+                // Entry written back by copy_entry_to_clipboard
+                let _ = request.done.send(pipeline.write_back(request.content));
+                continue;
+            }
+        };
 
-        if !crate::session::is_user_logged_in() {
-            // Skip clipboard processing if no user session
+        let capture_enabled = control::is_capture_enabled(&app_handle);
+        if !capture_enabled && matches!(wake, Wake::Clipboard | Wake::Settled) {
+            // Paused or incognito
+            settle.take();
+            pipeline.mark_current_as_seen();
             continue;
         }
 
-        let window_info = get_foreground_window_info();
-        let (source_app, source_window) = window_info.unwrap_or_else(|| {
-            ("Unknown".to_string(), "Unknown".to_string())
-        });
+        let Some(org_id) = crate::session::get_current_organization_id() else {
+            // Skip clipboard processing if no user session
+            settle.take();
+            continue;
+        };
+
+        let window_info = || {
+            get_foreground_window_info().unwrap_or_else(|| {
+                ("Unknown".to_string(), "Unknown".to_string())
+            })
+        };
+
+        let captured = match wake {
+            Wake::Clipboard => {
+                let window = pipeline.settle_window(&org_id).await;
+                if !window.is_zero() {
+                    // Polling ticks wake us too; only real changes count
+                    if !settle.changed(pipeline.clipboard_fingerprint()) {
+                        continue;
+                    }
+                    let (source_app, source_window) = window_info();
+                    settle.restart(window, source_app, source_window);
+                    continue;
+                }
+                let (source_app, source_window) = window_info();
+                pipeline
+                    .capture_once(&org_id, &source_app, &source_window)
+                    .await
+            }
+            Wake::Settled => {
+                let Some(copy) = settle.take() else {
+                    continue;
+                };
+                if copy.changes > 1 {
+                    println!("⏱️ Clipboard settled after {} rapid changes, keeping the last one", copy.changes);
+                }
+                pipeline
+                    .capture_once(&org_id, &copy.source_app, &copy.source_window)
+                    .await
+            }
+            Wake::Primary if !capture_enabled => {
+                pipeline.mark_primary_as_seen(&org_id).await;
+                continue;
+            }
+            Wake::Primary => {
+                let Some(text) = pipeline.poll_primary(&org_id).await else {
+                    continue;
+                };
+                let (source_app, source_window) = window_info();
+                pipeline
+                    .capture_primary(text, &org_id, &source_app, &source_window)
+                    .await
+            }
+        };
 
-        match clipboard.get_text() {
-            Ok(content) => {
-                if !content.trim().is_empty() && content != last_content {
-                    println!("📋 Clipboard text: '{}'", content);
-                    println!("📍 Source: '{}'", source_window);
+        if let Some(clipboard_content) = captured {
+            if let Err(e) = app_handle.emit("clipboard-update", &clipboard_content) {
+                println!("❌ Failed to emit clipboard event: {}", e);
+            }
+        }
+        if let Some(skipped) = pipeline.take_skipped() {
+            if let Err(e) = app_handle.emit("clipboard-skipped", &skipped) {
+                println!("❌ Failed to emit clipboard skipped event: {}", e);
+            }
+        }
+    }
+}
 
-                    let organization_id = crate::session::get_current_organization_id();
+/// Returns the PNG thumbnail of an image entry as a `data:` URL.
+#[tauri::command]
+pub async fn get_entry_thumbnail(
+    id: i64,
+    db_pools: State<'_, DbPools>,
+) -> Result<Option<String>, String> {
+    let entry = current_org_entry(&db_pools, id).await?;
+
+    match entry.image_path {
+        Some(file_name) if entry.content_type == "image" => {
+            image_store::thumbnail_data_url(&file_name).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
 
-                    if organization_id.is_none() {
-                        println!(
-                            "⚠️ No organization ID found - user not logged in, skipping clipboard save"
-                        );
-                        // last_content = content;
-                        continue;
-                    }
+/// Puts a history entry back on the system clipboard. With `paste`, also
+/// sends Ctrl+V to the window that had focus before ClipTray (X11 only).
+#[tauri::command]
+pub async fn copy_entry_to_clipboard(
+    id: i64,
+    paste: Option<bool>,
+    app_handle: AppHandle,
+    db_pools: State<'_, DbPools>,
+) -> Result<(), String> {
+    let entry = current_org_entry(&db_pools, id).await?;
 
-                    let org_id = organization_id.unwrap();
-                    println!("🏢 Organization ID for clipboard entry: {}", org_id);
-
-                    let mut new_entry = NewClipboardEntry::from_monitoring_data(
-                        content.clone(),
-                        source_app.clone(),
-                        source_window.clone(),
-                    );
-
-                    new_entry.organization_id = Some(org_id.clone());
-
-                    // Save to Postgres
-                    // 1️⃣ Always save to SQLite (offline-safe, no network needed)
-if let Err(e) = SqliteClipboardRepository::save_entry(&sqlite_pool, new_entry.clone()).await {
-    println!("❌ [SQLite] Failed to save clipboard entry: {}", e);
-} else {
-    println!("✅ [SQLite] Saved clipboard entry for organization: {}", org_id);
-}
-
-// 2️⃣ Try saving to Postgres *only if* pg_pool is available
-if let Some(ref pool) = pg_pool {
-    match ClipboardRepository::save_entry(pool, new_entry).await {
-        Ok(saved_entry) => {
-            println!(
-                "✅ [PG] Saved clipboard entry #{} for organization: {}",
-                saved_entry.id, org_id
-            );
+    let content = match entry.image_path.as_deref() {
+        Some(file_name) if entry.content_type == "image" => {
+            PasteContent::Image(image_store::load_image(file_name)?)
         }
-        Err(e) => {
-            println!("❌ [PG] Failed to save clipboard entry: {}", e);
+        _ if entry.content_type == "files" => PasteContent::Files(
+            entry.files().into_iter().map(|f| PathBuf::from(f.path)).collect(),
+        ),
+        _ => PasteContent::Text {
+            text: full_content(&entry, &db_pools).await?,
+            html: entry.content_html,
+        },
+    };
+
+    let writer = app_handle
+        .try_state::<ClipboardWriter>()
+        .ok_or_else(|| "Clipboard monitoring is not running".to_string())?;
+    writer.write(content).await?;
+    println!("📋 Copied entry {} back to the clipboard", id);
+
+    if paste.unwrap_or(false) {
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.hide();
         }
+
+        simulate_paste()
+            .await
+            .map_err(|e| format!("Copied to clipboard, but could not paste: {}", e))?;
     }
-} else {
-    println!("🌐 [PG] Skipped saving to Postgres (offline mode / no pool)");
+
+    Ok(())
 }
 
+/// Entry `id` of the signed-in organization. Entries of other
+/// organizations are reported as not found.
+async fn current_org_entry(db_pools: &DbPools, id: i64) -> Result<ClipboardEntry, String> {
+    let organization_id = crate::session::get_current_organization_id()
+        .ok_or_else(|| "User not logged in".to_string())?;
 
+    SqliteClipboardRepository::get_by_id_for_org(&db_pools.sqlite, id, &organization_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Clipboard entry not found".to_string())
+}
 
-                    let clipboard_content = ClipboardContent {
-                        text: content.clone(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        content_type: "text".to_string(),
-                        source_app: source_app.clone(),
-                        source_window: source_window.clone(),
-                    };
+/// Full text of an entry. Rows over the inline limit only hold a preview;
+/// their text is read from the blob store, or fetched from the cloud when
+/// the entry came from another device.
+pub async fn full_content(entry: &ClipboardEntry, db_pools: &DbPools) -> Result<String, String> {
+    let Some(hash) = entry.blob_ref.as_deref() else {
+        return Ok(entry.content.clone());
+    };
 
-                    if let Err(e) = app_handle.emit("clipboard-update", &clipboard_content) {
-                        println!("❌ Failed to emit clipboard event: {}", e);
-                    }
+    if blob_store::has_blob(hash) {
+        return blob_store::load_blob(hash);
+    }
 
-                    last_content = content;
-                }
-            }
-            Err(arboard::Error::ContentNotAvailable) => {
-                // Clipboard doesn't contain text content, this is normal
-            }
-            Err(e) => {
-                eprintln!("⚠️ Clipboard error: {}", e);
-                if let Ok(new_clipboard) = Clipboard::new() {
-                    clipboard = new_clipboard;
-                    println!("🔄 Clipboard reinitialized");
-                }
+    let pg_pool = db_pools
+        .pg
+        .as_ref()
+        .ok_or_else(|| "Full content is not on this device and the cloud is not available".to_string())?;
+    let organization_id = entry.organization_id.as_deref().unwrap_or_default();
+
+    let data = BlobsRepository::download(pg_pool, organization_id, hash)
+        .await
+        .map_err(|e| format!("Failed to download content: {}", e))?
+        .ok_or_else(|| "Full content has not been uploaded from its device yet".to_string())?;
+
+    println!("🗜️ Downloaded blob {} for entry {}", hash, entry.id);
+    blob_store::write_compressed(hash, &data)
+}
+
+/// The entry's complete text, for viewing or editing large copies whose
+/// row only holds a preview.
+#[tauri::command]
+pub async fn get_entry_content(
+    id: i64,
+    db_pools: State<'_, DbPools>,
+) -> Result<String, String> {
+    let entry = current_org_entry(&db_pools, id).await?;
+
+    full_content(&entry, &db_pools).await
+}
+
+/// The whole entry behind a history list summary, with the complete text
+/// in `content` even when the row only holds a preview.
+#[tauri::command]
+pub async fn get_full_entry(
+    id: i64,
+    db_pools: State<'_, DbPools>,
+) -> Result<ClipboardEntry, String> {
+    let mut entry = current_org_entry(&db_pools, id).await?;
+
+    entry.content = full_content(&entry, &db_pools).await?;
+    Ok(entry)
+}
+
+/// Files of a "files" entry, re-checked on disk so moved or deleted files
+/// show up as missing.
+#[tauri::command]
+pub async fn get_entry_files(
+    id: i64,
+    db_pools: State<'_, DbPools>,
+) -> Result<Vec<FileRef>, String> {
+    let entry = current_org_entry(&db_pools, id).await?;
+
+    if entry.content_type != "files" {
+        return Ok(Vec::new());
+    }
+
+    Ok(entry
+        .files()
+        .into_iter()
+        .map(|captured| {
+            let current = FileRef::stat(Path::new(&captured.path));
+            FileRef {
+                // Keep the size seen at capture time for files that are gone
+                size: current.size.or(captured.size.filter(|_| !current.exists)),
+                ..current
             }
-        }
+        })
+        .collect())
+}
+
+/// How the source app of a copy is determined on this system.
+#[tauri::command]
+pub fn get_window_detection_method() -> WindowDetection {
+    foreground::detection_method()
+}
+
+#[tauri::command]
+pub fn pause_clipboard_capture(app_handle: AppHandle) -> CaptureStatus {
+    control::pause(&app_handle, None)
+}
+
+#[tauri::command]
+pub fn resume_clipboard_capture(app_handle: AppHandle) -> CaptureStatus {
+    control::resume(&app_handle)
+}
+
+/// Stops capture for `minutes`, then resumes automatically.
+#[tauri::command]
+pub fn start_incognito_mode(app_handle: AppHandle, minutes: u64) -> Result<CaptureStatus, String> {
+    if minutes == 0 {
+        return Err("Incognito duration must be at least one minute".to_string());
     }
+
+    Ok(control::pause(&app_handle, Some(Duration::from_secs(minutes * 60))))
+}
+
+#[tauri::command]
+pub fn get_clipboard_capture_status(app_handle: AppHandle) -> CaptureStatus {
+    control::current_status(&app_handle)
 }
@@ -0,0 +1,165 @@
+// src/db/blob_store.rs
+//
+// Copies larger than the organization's inline limit (log dumps, base64
+// blobs) are kept out of `clipboard_entries.content`. The full text is stored
+// zstd-compressed in a `blobs/` folder next to the SQLite database, named by
+// its SHA-256, and the row keeps a preview plus that hash in `blob_ref`.
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+use crate::db::sqlite_database::get_data_dir;
+
+/// Characters of a large copy kept inline for lists and search.
+const PREVIEW_CHARS: usize = 2000;
+const COMPRESSION_LEVEL: i32 = 3;
+/// Largest text a downloaded blob may decompress to. Those bytes come from
+/// the cloud, and a few kilobytes of zstd can expand to gigabytes.
+const MAX_BLOB_BYTES: u64 = 64 * 1024 * 1024;
+/// Blob files younger than this are never collected; their row may not be
+/// written yet.
+const ORPHAN_GRACE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub hash: String,
+    /// Size of the original text
+    pub byte_size: u64,
+    /// Size of the compressed file on disk
+    pub stored_size: u64,
+}
+
+pub fn blobs_dir() -> PathBuf {
+    let dir = get_data_dir().join("blobs");
+    if !dir.exists() {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("Warning: Failed to create blobs directory: {}", e);
+        }
+    }
+    dir
+}
+
+fn blob_path(hash: &str) -> PathBuf {
+    blobs_dir().join(format!("{}.zst", hash))
+}
+
+pub fn hash_blob(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Start of the text, cut on a character boundary.
+pub fn preview(content: &str) -> String {
+    match content.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => content[..end].to_string(),
+        None => content.to_string(),
+    }
+}
+
+/// Compresses and writes the text unless a blob with the same hash exists.
+pub fn save_blob(content: &str) -> Result<StoredBlob, Box<dyn std::error::Error>> {
+    let hash = hash_blob(content);
+    let path = blob_path(&hash);
+
+    if !path.exists() {
+        let compressed = zstd::encode_all(content.as_bytes(), COMPRESSION_LEVEL)?;
+        // Write then rename, so a crash never leaves a truncated blob behind
+        let tmp = path.with_extension("zst.tmp");
+        fs::write(&tmp, compressed)?;
+        fs::rename(&tmp, &path)?;
+    }
+
+    Ok(StoredBlob {
+        hash,
+        byte_size: content.len() as u64,
+        stored_size: fs::metadata(&path)?.len(),
+    })
+}
+
+pub fn has_blob(hash: &str) -> bool {
+    blob_path(hash).exists()
+}
+
+pub fn load_blob(hash: &str) -> Result<String, String> {
+    let compressed = fs::read(blob_path(hash))
+        .map_err(|e| format!("Failed to read blob {}: {}", hash, e))?;
+    decompress(&compressed)
+}
+
+/// Compressed bytes as stored on disk, for upload.
+pub fn read_compressed(hash: &str) -> Result<Vec<u8>, String> {
+    fs::read(blob_path(hash)).map_err(|e| format!("Failed to read blob {}: {}", hash, e))
+}
+
+/// Stores compressed bytes downloaded from the cloud after checking they
+/// decompress, within the size limit, to the expected hash.
+pub fn write_compressed(hash: &str, compressed: &[u8]) -> Result<String, String> {
+    let content = decompress_bounded(compressed)?;
+    if hash_blob(&content) != hash {
+        return Err(format!("Blob {} failed its integrity check", hash));
+    }
+
+    let path = blob_path(hash);
+    let tmp = path.with_extension("zst.tmp");
+    fs::write(&tmp, compressed)
+        .and_then(|()| fs::rename(&tmp, &path))
+        .map_err(|e| format!("Failed to write blob {}: {}", hash, e))?;
+
+    Ok(content)
+}
+
+/// Deletes blob files no entry references. Returns how many were removed.
+pub fn remove_orphans(referenced: &HashSet<String>) -> usize {
+    let Ok(entries) = fs::read_dir(blobs_dir()) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(hash) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".zst")) else {
+            continue;
+        };
+        if referenced.contains(hash) {
+            continue;
+        }
+
+        let old_enough = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= ORPHAN_GRACE);
+
+        if old_enough && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+fn decompress(compressed: &[u8]) -> Result<String, String> {
+    let bytes = zstd::decode_all(compressed).map_err(|e| format!("Failed to decompress blob: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("Blob is not valid UTF-8: {}", e))
+}
+
+/// Like `decompress`, but stops once the output passes `MAX_BLOB_BYTES`.
+fn decompress_bounded(compressed: &[u8]) -> Result<String, String> {
+    let decoder = zstd::stream::read::Decoder::new(compressed)
+        .map_err(|e| format!("Failed to decompress blob: {}", e))?;
+
+    let mut bytes = Vec::new();
+    decoder
+        .take(MAX_BLOB_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to decompress blob: {}", e))?;
+    if bytes.len() as u64 > MAX_BLOB_BYTES {
+        return Err(format!("Blob is larger than {} bytes", MAX_BLOB_BYTES));
+    }
+
+    String::from_utf8(bytes).map_err(|e| format!("Blob is not valid UTF-8: {}", e))
+}
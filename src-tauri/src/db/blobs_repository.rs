@@ -0,0 +1,45 @@
+// src/db/blobs_repository.rs
+
+use sqlx::{PgPool, Row};
+
+pub struct BlobsRepository;
+
+impl BlobsRepository {
+    /// ✅ Upload a compressed blob; already uploaded blobs are left alone
+    pub async fn upload(
+        pool: &PgPool,
+        organization_id: &str,
+        hash: &str,
+        data: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_blobs (organization_id, hash, data)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (organization_id, hash) DO NOTHING
+            "#,
+        )
+        .bind(organization_id)
+        .bind(hash)
+        .bind(data)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// ✅ Compressed blob, if it was uploaded yet
+    pub async fn download(
+        pool: &PgPool,
+        organization_id: &str,
+        hash: &str,
+    ) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        let row = sqlx::query("SELECT data FROM content_blobs WHERE organization_id = $1 AND hash = $2")
+            .bind(organization_id)
+            .bind(hash)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(row.map(|row| row.get("data")))
+    }
+}
@@ -0,0 +1,113 @@
+// src/db/capture_rules_repository.rs
+
+use crate::db::schemas::capture_rules::{CaptureRule, LocalCaptureRule};
+use sqlx::{PgPool, Row};
+
+pub struct CaptureRulesRepository;
+
+impl CaptureRulesRepository {
+    /// ✅ Cloud copies of the organization's rules
+    pub async fn get_for_org(
+        pool: &PgPool,
+        organization_id: &str,
+    ) -> Result<Vec<CaptureRule>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, organization_id, name, enabled, position, conditions, actions, created_at, updated_at
+            FROM capture_rules
+            WHERE organization_id = $1
+            ORDER BY position ASC, id ASC
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let conditions: String = row.get("conditions");
+                let actions: String = row.get("actions");
+                CaptureRule {
+                    id: row.get("id"),
+                    organization_id: row.get("organization_id"),
+                    name: row.get("name"),
+                    enabled: row.get("enabled"),
+                    position: row.get("position"),
+                    conditions: serde_json::from_str(&conditions).unwrap_or_default(),
+                    actions: serde_json::from_str(&actions).unwrap_or_default(),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                }
+            })
+            .collect())
+    }
+
+    /// ✅ Pushes a local rule; returns the cloud id. A rule whose cloud row
+    /// has disappeared is inserted again.
+    pub async fn upsert_from_local(
+        pool: &PgPool,
+        local: &LocalCaptureRule,
+    ) -> Result<i64, sqlx::Error> {
+        if let Some(server_id) = local.server_id {
+            let updated = sqlx::query(
+                r#"
+                UPDATE capture_rules
+                SET name = $1, enabled = $2, position = $3, conditions = $4, actions = $5, updated_at = $6
+                WHERE id = $7 AND organization_id = $8
+                RETURNING id
+                "#,
+            )
+            .bind(&local.name)
+            .bind(local.enabled)
+            .bind(local.position)
+            .bind(&local.conditions)
+            .bind(&local.actions)
+            .bind(local.updated_at)
+            .bind(server_id)
+            .bind(&local.organization_id)
+            .fetch_optional(pool)
+            .await?;
+
+            if let Some(row) = updated {
+                return Ok(row.get("id"));
+            }
+        }
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO capture_rules
+                (organization_id, name, enabled, position, conditions, actions, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(&local.organization_id)
+        .bind(&local.name)
+        .bind(local.enabled)
+        .bind(local.position)
+        .bind(&local.conditions)
+        .bind(&local.actions)
+        .bind(local.created_at)
+        .bind(local.updated_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(row.get("id"))
+    }
+
+    /// ✅ Delete a rule from the cloud
+    pub async fn delete(
+        pool: &PgPool,
+        server_id: i64,
+        organization_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM capture_rules WHERE id = $1 AND organization_id = $2")
+            .bind(server_id)
+            .bind(organization_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
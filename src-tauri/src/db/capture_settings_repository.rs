@@ -0,0 +1,50 @@
+// src/db/capture_settings_repository.rs
+
+use crate::db::schemas::capture_settings::CaptureSettings;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+
+pub struct CaptureSettingsRepository;
+
+impl CaptureSettingsRepository {
+    /// ✅ Cloud copy of the organization's settings and when it was last changed
+    pub async fn get_for_org(
+        pool: &PgPool,
+        organization_id: &str,
+    ) -> Result<Option<(CaptureSettings, DateTime<Utc>)>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT settings, updated_at FROM capture_settings WHERE organization_id = $1",
+        )
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(row.map(|row| {
+            let settings: String = row.get("settings");
+            (CaptureSettings::from_json(&settings), row.get("updated_at"))
+        }))
+    }
+
+    /// ✅ Insert or replace the organization's settings
+    pub async fn upsert_for_org(
+        pool: &PgPool,
+        organization_id: &str,
+        settings: &CaptureSettings,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO capture_settings (organization_id, settings, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (organization_id) DO UPDATE SET
+                settings = EXCLUDED.settings,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(organization_id)
+        .bind(settings.to_json())
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
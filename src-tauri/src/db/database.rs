@@ -1,7 +1,11 @@
 // src/db/database.rs
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use crate::db::schemas::{ClipboardEntry, NewClipboardEntry, UpdateClipboardEntry};
+use crate::db::schemas::clipboard::{content_hash, tag_names};
+use crate::db::entry_tags_repository::EntryTagsRepository;
+use crate::capture::classifier;
 use crate::config::{get_database_url};
+use crate::db::migrations;
 use serde_json;
 use std::time::Duration;
 
@@ -40,160 +44,11 @@ pub async fn create_db_pool() -> Result<PgPool, Box<dyn std::error::Error>> {
         .execute(&pool)
         .await?;    
     println!("Database connected successfully!");    
-    // Create tables if they don't exist
-    create_tables(&pool).await?;    
+    // Create tables or bring the cloud schema up to date
+    migrations::run_migrations(&pool).await?;
     Ok(pool)
 }
 
-pub async fn create_tables(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
-    println!("📝 Creating database tables if they don't exist...");
-
-    println!("📝 Creating purge_cadence enum type if not exists...");
-    sqlx::query(
-        r#"
-        DO $$ 
-        BEGIN
-            CREATE TYPE purge_cadence AS ENUM (
-                'never',
-                'every_24_hours', 
-                'every_3_days',
-                'every_week',
-                'every_month'
-            );
-        EXCEPTION
-            WHEN duplicate_object THEN null;
-        END $$;
-        "#
-    )
-    .execute(pool)
-    .await?;
-
-    // Note: payment_status is VARCHAR(20) to match NestJS backend schema, not an enum type
-
-    println!("📝 Creating Users table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id BIGSERIAL PRIMARY KEY,
-            organization_id VARCHAR(255) NOT NULL,
-            firebase_uid TEXT UNIQUE NOT NULL,
-            email TEXT NOT NULL,
-            display_name TEXT,
-            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            purge_cadence purge_cadence NOT NULL DEFAULT 'every_24_hours',
-            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            last_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            retain_tags BOOLEAN NOT NULL DEFAULT FALSE
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
-
-    println!("📝 Creating clipboard table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS clipboard_entries (
-            id BIGSERIAL PRIMARY KEY,
-            organization_id VARCHAR(255) NOT NULL,
-            content TEXT NOT NULL,
-            content_type VARCHAR(50) NOT NULL DEFAULT 'text',
-            content_hash VARCHAR(64) UNIQUE NOT NULL,
-            source_app VARCHAR(255) NOT NULL,
-            source_window VARCHAR(255) NOT NULL,
-            timestamp TIMESTAMPTZ NOT NULL,
-            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            tags TEXT,
-            is_pinned BOOLEAN NOT NULL DEFAULT FALSE
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
-
-    println!("📝 Creating Tags table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tags (
-            id BIGSERIAL PRIMARY KEY,
-            organization_id VARCHAR(255) NOT NULL,
-            name VARCHAR(100) NOT NULL,
-            color VARCHAR(7) NOT NULL DEFAULT '#6B7280',
-            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
-
-    println!("📝 Creating Payments table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS payments (
-            id BIGSERIAL PRIMARY KEY,
-            stripe_session_id VARCHAR(255) UNIQUE NOT NULL,
-            stripe_payment_intent_id VARCHAR(255),
-            organization_id VARCHAR(255) NOT NULL,
-            firebase_uid VARCHAR(255) NOT NULL,
-            email VARCHAR(255) NOT NULL,
-            amount_paid INTEGER NOT NULL DEFAULT 0,
-            currency VARCHAR(10) NOT NULL DEFAULT 'usd',
-            payment_status VARCHAR(20) NOT NULL DEFAULT 'unpaid',
-            plan_type VARCHAR(50) NOT NULL DEFAULT 'lifetime',
-            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-            paid_at TIMESTAMPTZ,
-            metadata JSONB
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
-
-    // === Indexes ===
-    println!("📝 Creating indexes if not exist...");
-    
-    // Users indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_firebase_uid ON users(firebase_uid)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_organization_id ON users(organization_id)")
-        .execute(pool).await?;
-
-    // Clipboard indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_content_hash ON clipboard_entries(content_hash)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_created_at ON clipboard_entries(created_at DESC)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_source_app ON clipboard_entries(source_app)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_organization_id ON clipboard_entries(organization_id)")
-        .execute(pool).await?;
-
-    // Tags indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_organization_id ON tags(organization_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name)")
-        .execute(pool).await?;
-    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_organization_name_unique ON tags(organization_id, LOWER(name))")
-        .execute(pool).await?;
-
-    // Payments indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_firebase_uid ON payments(firebase_uid)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_organization_id ON payments(organization_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_stripe_session_id ON payments(stripe_session_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_status ON payments(payment_status)")
-        .execute(pool).await?;
-    
-    println!("✅ Database tables ready!");
-    Ok(())
-}
-
 pub fn tags_to_json(tag_names: &[String]) -> Option<String> {
     if tag_names.is_empty() {
         None
@@ -252,22 +107,30 @@ impl ClipboardRepository {
         pool: &PgPool, 
         entry: NewClipboardEntry
     ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-        // Idempotent upsert by content_hash
+        // Idempotent upsert by (organization_id, content_hash)
         let result = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             INSERT INTO clipboard_entries 
-                (content, content_type, content_hash, source_app, source_window, timestamp, tags, organization_id, is_pinned)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            ON CONFLICT (content_hash) DO UPDATE
+                (content, content_type, content_hash, source_app, source_window, timestamp, organization_id, is_pinned,
+                 image_width, image_height, byte_size, copy_count, last_source_app, content_html, content_meta, blob_ref)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, COALESCE($13, $4), $14, $15, $16)
+            ON CONFLICT (organization_id, content_hash) DO UPDATE
             SET
                 content        = EXCLUDED.content,
                 content_type   = EXCLUDED.content_type,
-                source_app     = EXCLUDED.source_app,
-                source_window  = EXCLUDED.source_window,
+                source_app     = clipboard_entries.source_app,
+                source_window  = clipboard_entries.source_window,
+                last_source_app = EXCLUDED.source_app,
+                copy_count     = GREATEST(clipboard_entries.copy_count, EXCLUDED.copy_count),
                 timestamp      = EXCLUDED.timestamp,
-                tags           = COALESCE(EXCLUDED.tags, clipboard_entries.tags),
                 organization_id = EXCLUDED.organization_id,
-                is_pinned    =  EXCLUDED.is_pinned
+                is_pinned    = clipboard_entries.is_pinned OR EXCLUDED.is_pinned,
+                image_width  = EXCLUDED.image_width,
+                image_height = EXCLUDED.image_height,
+                byte_size    = EXCLUDED.byte_size,
+                content_html = EXCLUDED.content_html,
+                content_meta = EXCLUDED.content_meta,
+                blob_ref     = EXCLUDED.blob_ref
             RETURNING *
             "#
         )
@@ -277,11 +140,25 @@ impl ClipboardRepository {
         .bind(entry.source_app)
         .bind(entry.source_window)
         .bind(entry.timestamp)
-        .bind(entry.tags)
-        .bind(entry.organization_id)
+        .bind(&entry.organization_id)
         .bind(entry.is_pinned)
+        .bind(entry.image_width)
+        .bind(entry.image_height)
+        .bind(entry.byte_size)
+        .bind(entry.copy_count)
+        .bind(entry.last_source_app)
+        .bind(entry.content_html)
+        .bind(entry.content_meta)
+        .bind(entry.blob_ref)
         .fetch_one(pool)
         .await?;
+
+        // A pushed entry without tags leaves the cloud row's tags alone
+        if let Some(tags) = entry.tags {
+            let organization_id = entry.organization_id.unwrap_or_default();
+            EntryTagsRepository::set_names(pool, result.id, &organization_id, &tag_names(Some(&tags))).await?;
+            return Ok(Self::get_by_id(pool, result.id).await?.unwrap_or(result));
+        }
         
         Ok(result)
     }
@@ -374,17 +251,21 @@ impl ClipboardRepository {
             r#"
             UPDATE clipboard_entries 
             SET 
-                is_pinned = COALESCE($1, is_pinned),
-                tags = COALESCE($2, tags)
-            WHERE id = $3
+                is_pinned = COALESCE($1, is_pinned)
+            WHERE id = $2
             RETURNING *
             "#
         )
         .bind(update.is_pinned)
-        .bind(update.tags)
         .bind(id)
         .fetch_one(pool)
         .await?;
+
+        if let Some(tags) = update.tags {
+            let organization_id = result.organization_id.clone().unwrap_or_default();
+            EntryTagsRepository::set_names(pool, id, &organization_id, &tag_names(Some(&tags))).await?;
+            return Ok(Self::get_by_id(pool, id).await?.unwrap_or(result));
+        }
         
         Ok(result)
     }
@@ -408,23 +289,29 @@ impl ClipboardRepository {
         server_id: i64,
         local: &ClipboardEntry,
     ) -> Result<ClipboardEntry, sqlx::Error> {
-        sqlx::query_as::<_, ClipboardEntry>(
+        let updated = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             UPDATE clipboard_entries
             SET
                 is_pinned = $1,
-                tags      = $2,
-                timestamp = $3
-            WHERE id = $4
+                timestamp = $2
+            WHERE id = $3
             RETURNING *
             "#
         )
         .bind(local.is_pinned)
-        .bind(&local.tags)
         .bind(local.timestamp)
         .bind(server_id)
         .fetch_one(pool)
-        .await
+        .await?;
+
+        let organization_id = updated.organization_id.clone().unwrap_or_default();
+        EntryTagsRepository::set_names(pool, server_id, &organization_id, &local.tag_names()).await?;
+
+        sqlx::query_as::<_, ClipboardEntry>("SELECT * FROM clipboard_entries WHERE id = $1")
+            .bind(server_id)
+            .fetch_one(pool)
+            .await
     }
 
     pub async fn delete_entry_for_org(
@@ -451,21 +338,18 @@ impl ClipboardRepository {
         entry_id: i64,
         new_content: &str,
     ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-        let content_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let mut hasher = DefaultHasher::new();
-            new_content.hash(&mut hasher);
-            format!("{:x}", hasher.finish())
-        };
-        
+        let content_hash = content_hash(new_content);
+        let classification = classifier::classify(new_content);
+
         let result = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             UPDATE clipboard_entries 
             SET 
                 content = $1,
                 content_hash = $2,
-                timestamp = $3
+                timestamp = $3,
+                content_type = $5,
+                content_meta = $6
             WHERE id = $4
             RETURNING *
             "#
@@ -474,6 +358,8 @@ impl ClipboardRepository {
         .bind(content_hash)
         .bind(chrono::Utc::now())
         .bind(entry_id)
+        .bind(&classification.content_type)
+        .bind(classification.metadata_json())
         .fetch_one(pool)
         .await?;
         
@@ -482,11 +368,13 @@ impl ClipboardRepository {
     
     pub async fn exists_by_hash(
         pool: &PgPool, 
+        organization_id: &str,
         content_hash: &str
     ) -> Result<bool, Box<dyn std::error::Error>> {
         let result = sqlx::query(
-            "SELECT 1 FROM clipboard_entries WHERE content_hash = $1 LIMIT 1"
+            "SELECT 1 FROM clipboard_entries WHERE organization_id = $1 AND content_hash = $2 LIMIT 1"
         )
+        .bind(organization_id)
         .bind(content_hash)
         .fetch_optional(pool)
         .await?;
@@ -522,7 +410,8 @@ impl ClipboardRepository {
 
     pub async fn delete_untagged_entries(pool: &PgPool, organization_id: &str) -> Result<usize, sqlx::Error> {
         sqlx::query(
-            "DELETE FROM clipboard_entries WHERE organization_id = $1 AND is_pinned = false AND tags IS NULL"
+            "DELETE FROM clipboard_entries WHERE organization_id = $1 AND is_pinned = false
+               AND NOT EXISTS (SELECT 1 FROM entry_tags WHERE entry_id = clipboard_entries.id)"
         )
         .bind(organization_id)
         .execute(pool)
@@ -541,128 +430,61 @@ impl ClipboardRepository {
     }
 
     pub async fn assign_tag(
-    pool: &PgPool, 
-    clipboard_entry_id: i64, 
-    tag_name: &str
-) -> Result<ClipboardEntry, String> { // Change to String error type
-    println!("=== ASSIGN TAG DEBUG ===");
-    println!("🟢 Assigning tag '{}' to entry {}", tag_name, clipboard_entry_id);
-    
-    // First get the current entry
-    let current_entry = Self::get_by_id(pool, clipboard_entry_id).await
-        .map_err(|e| format!("Database error: {}", e))?
-        .ok_or("Clipboard entry not found".to_string())?;
-    
-    println!("📋 Current entry tags: {:?}", current_entry.tags);
-    
-    // Parse current tags
-    let mut current_tags = json_to_tags(&current_entry.tags);
-    println!("📋 Parsed current tags: {:?}", current_tags);
-    
-    // Add the new tag name if not already present
-    if !current_tags.contains(&tag_name.to_string()) {
-        current_tags.push(tag_name.to_string());
-        println!("✅ Added tag '{}'", tag_name);
-    } else {
-        println!("ℹ️ Tag '{}' already exists", tag_name);
+        pool: &PgPool,
+        clipboard_entry_id: i64,
+        tag_name: &str
+    ) -> Result<ClipboardEntry, String> {
+        println!("🟢 Assigning tag '{}' to entry {}", tag_name, clipboard_entry_id);
+
+        let entry = Self::get_for_current_organization(pool, clipboard_entry_id).await?;
+        let organization_id = entry.organization_id.clone().unwrap_or_default();
+
+        let mut names = entry.tag_names();
+        if !names.iter().any(|name| name.eq_ignore_ascii_case(tag_name.trim())) {
+            names.push(tag_name.trim().to_string());
+        }
+        EntryTagsRepository::set_names(pool, clipboard_entry_id, &organization_id, &names)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))?;
+
+        let result = Self::get_for_current_organization(pool, clipboard_entry_id).await?;
+        println!("✅ Entry {} tags: {:?}", clipboard_entry_id, result.tag_names());
+        Ok(result)
     }
-    
-    println!("📋 New tags: {:?}", current_tags);
-    
-    // Convert back to JSON
-    let new_tags_json = tags_to_json(&current_tags);
-    println!("📋 New tags JSON: {:?}", new_tags_json);
-    
-    // Update the entry
-    let update = UpdateClipboardEntry {
-        tags: new_tags_json,
-        is_pinned: None,
-    };
-    
-    let result = Self::update_entry(pool, clipboard_entry_id, update).await
-        .map_err(|e| format!("Update failed: {}", e))?;
-    
-    println!("✅ Success! Updated entry tags: {:?}", json_to_tags(&result.tags));
-    println!("=== ASSIGN TAG DEBUG END ===");
-    
-    Ok(result)
-}
 
-pub async fn remove_tag(
-    pool: &PgPool, 
-    clipboard_entry_id: i64, 
-    tag_name: &str
-) -> Result<ClipboardEntry, String> {
-    println!("=== REMOVE TAG DEBUG ===");
-    println!("🔴 Removing tag '{}' from entry {}", tag_name, clipboard_entry_id);
-    
-    // First get the current entry WITH ORGANIZATION CHECK
-    let organization_id = crate::session::get_current_organization_id()
-        .ok_or("User not logged in".to_string())?;
-    
-    let current_entry = sqlx::query_as::<_, ClipboardEntry>(
-        "SELECT * FROM clipboard_entries WHERE id = $1 AND organization_id = $2"
-    )
-    .bind(clipboard_entry_id)
-    .bind(&organization_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| format!("Database error: {}", e))?
-    .ok_or("Clipboard entry not found".to_string())?;
-    
-    println!("📋 Current entry tags: {:?}", current_entry.tags);
-    
-    // Parse current tags and remove the specified tag name
-    let mut current_tags = json_to_tags(&current_entry.tags);
-    println!("📋 Parsed current tags: {:?}", current_tags);
-    
-    let before_count = current_tags.len();
-    current_tags.retain(|name| name != tag_name);
-    let after_count = current_tags.len();
-    
-    println!("📋 Tags before: {}, after: {}", before_count, after_count);
-    println!("📋 New tags: {:?}", current_tags);
-    
-    // Convert back to JSON
-    let new_tags_json = tags_to_json(&current_tags);
-    println!("📋 New tags JSON: {:?}", new_tags_json);
-    
-    // Update the entry WITH ORGANIZATION CHECK
-    let result = sqlx::query_as::<_, ClipboardEntry>(
-        r#"
-        UPDATE clipboard_entries 
-        SET tags = $1
-        WHERE id = $2 AND organization_id = $3
-        RETURNING *
-        "#
-    )
-    .bind(&new_tags_json)
-    .bind(clipboard_entry_id)
-    .bind(&organization_id)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| format!("Update failed: {}", e))?;
-    
-    println!("✅ Database update successful!");
-    println!("✅ Updated entry tags from query: {:?}", result.tags);
-    
-    // Verify with the same organization check
-    let verified_entry = sqlx::query_as::<_, ClipboardEntry>(
-        "SELECT * FROM clipboard_entries WHERE id = $1 AND organization_id = $2"
-    )
-    .bind(clipboard_entry_id)
-    .bind(&organization_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| format!("Verification failed: {}", e))?
-    .ok_or("Could not verify update - entry not found".to_string())?;
-    
-    let verified_tags = json_to_tags(&verified_entry.tags);
-    println!("✅ Verified tags after update: {:?}", verified_tags);
-    println!("✅ Raw verified tags: {:?}", verified_entry.tags);
-    
-    println!("=== REMOVE TAG DEBUG END ===");
-    Ok(result)
-}
+    pub async fn remove_tag(
+        pool: &PgPool,
+        clipboard_entry_id: i64,
+        tag_name: &str
+    ) -> Result<ClipboardEntry, String> {
+        println!("🔴 Removing tag '{}' from entry {}", tag_name, clipboard_entry_id);
+
+        let entry = Self::get_for_current_organization(pool, clipboard_entry_id).await?;
+        let mut names = entry.tag_names();
+        names.retain(|name| !name.eq_ignore_ascii_case(tag_name.trim()));
+
+        EntryTagsRepository::set_names(pool, clipboard_entry_id, &entry.organization_id.unwrap_or_default(), &names)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))?;
+
+        let result = Self::get_for_current_organization(pool, clipboard_entry_id).await?;
+        println!("✅ Entry {} tags: {:?}", clipboard_entry_id, result.tag_names());
+        Ok(result)
+    }
+
+    async fn get_for_current_organization(pool: &PgPool, clipboard_entry_id: i64) -> Result<ClipboardEntry, String> {
+        let organization_id = crate::session::get_current_organization_id()
+            .ok_or("User not logged in".to_string())?;
+
+        sqlx::query_as::<_, ClipboardEntry>(
+            "SELECT * FROM clipboard_entries WHERE id = $1 AND organization_id = $2"
+        )
+        .bind(clipboard_entry_id)
+        .bind(&organization_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("Clipboard entry not found".to_string())
+    }
 
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+// src/db/entry_tags_repository.rs
+//
+// Cloud side of `entry_tags`; see sqlite_entry_tags_repository. Triggers from
+// the `005_entry_tags` migration keep `clipboard_entries.tags` in step.
+use std::collections::HashSet;
+
+use sqlx::{PgConnection, PgPool};
+
+pub struct EntryTagsRepository;
+
+impl EntryTagsRepository {
+    /// Makes the named tags the entry's only tags.
+    pub async fn set_names(
+        pool: &PgPool,
+        entry_id: i64,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let keep = Self::tag_ids_for_names(&mut tx, organization_id, names).await?;
+
+        sqlx::query("DELETE FROM entry_tags WHERE entry_id = $1 AND NOT (tag_id = ANY($2))")
+            .bind(entry_id)
+            .bind(&keep)
+            .execute(&mut *tx)
+            .await?;
+        Self::link_ids(&mut tx, entry_id, &keep).await?;
+
+        tx.commit().await
+    }
+
+    async fn link_ids(conn: &mut PgConnection, entry_id: i64, tag_ids: &[i64]) -> Result<(), sqlx::Error> {
+        for tag_id in tag_ids {
+            sqlx::query("INSERT INTO entry_tags (entry_id, tag_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(entry_id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Ids of the organization's tags with these names (case-insensitive),
+    /// creating the missing ones.
+    async fn tag_ids_for_names(
+        conn: &mut PgConnection,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if !seen.insert(name.to_lowercase()) {
+                continue;
+            }
+
+            sqlx::query("INSERT INTO tags (organization_id, name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(organization_id)
+                .bind(name)
+                .execute(&mut *conn)
+                .await?;
+
+            let id: i64 = sqlx::query_scalar(
+                "SELECT id FROM tags WHERE organization_id = $1 AND LOWER(name) = LOWER($2)",
+            )
+            .bind(organization_id)
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+}
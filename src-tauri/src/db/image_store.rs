@@ -0,0 +1,175 @@
+// src/db/image_store.rs
+//
+// Captured clipboard images live as PNG files in an `images/` folder next to
+// the SQLite database. Rows only keep the file name, so the data directory
+// can be moved without rewriting the table.
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use arboard::ImageData;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use image::{imageops, ImageFormat, RgbaImage};
+use sha2::{Digest, Sha256};
+
+use crate::db::sqlite_database::get_data_dir;
+
+const THUMBNAIL_MAX_SIZE: u32 = 256;
+/// Image files younger than this are never collected; their row may not be
+/// written yet.
+const ORPHAN_GRACE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub file_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub byte_size: u64,
+}
+
+pub fn images_dir() -> PathBuf {
+    let dir = get_data_dir().join("images");
+    if !dir.exists() {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("Warning: Failed to create images directory: {}", e);
+        }
+    }
+    dir
+}
+
+/// Hash of the raw pixels plus dimensions, used as the entry's content_hash.
+pub fn hash_image(image: &ImageData) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update((image.width as u64).to_le_bytes());
+    hasher.update((image.height as u64).to_le_bytes());
+    hasher.update(&image.bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Encodes the clipboard image as PNG (plus a thumbnail) unless a file for
+/// the same hash already exists.
+pub fn save_image(
+    content_hash: &str,
+    image: &ImageData,
+) -> Result<StoredImage, Box<dyn std::error::Error>> {
+    let width = image.width as u32;
+    let height = image.height as u32;
+
+    let file_name = format!("{}.png", content_hash);
+    let path = images_dir().join(&file_name);
+
+    if !path.exists() {
+        let rgba = RgbaImage::from_raw(width, height, image.bytes.to_vec())
+            .ok_or("Clipboard image buffer does not match its dimensions")?;
+
+        rgba.save_with_format(&path, ImageFormat::Png)?;
+        write_thumbnail(&rgba, &file_name)?;
+    }
+
+    let byte_size = fs::metadata(&path)?.len();
+
+    Ok(StoredImage {
+        file_name,
+        width,
+        height,
+        byte_size,
+    })
+}
+
+/// Decodes a stored image back into clipboard pixels.
+pub fn load_image(file_name: &str) -> Result<ImageData<'static>, String> {
+    let rgba = image::open(images_dir().join(file_name))
+        .map_err(|e| format!("Failed to open image {}: {}", file_name, e))?
+        .to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    Ok(ImageData {
+        width: width as usize,
+        height: height as usize,
+        bytes: rgba.into_raw().into(),
+    })
+}
+
+/// Returns the entry's thumbnail as a `data:` URL the webview can render
+/// directly. Missing thumbnails are regenerated from the full image.
+pub fn thumbnail_data_url(file_name: &str) -> Result<String, String> {
+    let thumb_path = images_dir().join(thumbnail_file_name(file_name));
+
+    if !thumb_path.exists() {
+        let full = image::open(images_dir().join(file_name))
+            .map_err(|e| format!("Failed to open image {}: {}", file_name, e))?
+            .to_rgba8();
+        write_thumbnail(&full, file_name)
+            .map_err(|e| format!("Failed to create thumbnail: {}", e))?;
+    }
+
+    let bytes = fs::read(&thumb_path)
+        .map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(bytes)))
+}
+
+/// Removes the image and its thumbnail. Missing files are not an error.
+pub fn delete_image(file_name: &str) {
+    let dir = images_dir();
+    let _ = fs::remove_file(dir.join(file_name));
+    let _ = fs::remove_file(dir.join(thumbnail_file_name(file_name)));
+}
+
+/// Deletes images (and their thumbnails) no entry references. Returns how
+/// many images were removed.
+pub fn remove_orphans(referenced: &HashSet<String>) -> usize {
+    let Ok(entries) = fs::read_dir(images_dir()) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // Thumbnails go with their image
+        if !name.ends_with(".png") || name.ends_with("_thumb.png") || referenced.contains(name) {
+            continue;
+        }
+
+        let old_enough = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= ORPHAN_GRACE);
+
+        if old_enough {
+            delete_image(name);
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+fn thumbnail_file_name(file_name: &str) -> String {
+    let stem = file_name.trim_end_matches(".png");
+    format!("{}_thumb.png", stem)
+}
+
+fn write_thumbnail(
+    image: &RgbaImage,
+    file_name: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, height) = image.dimensions();
+    let scale = (THUMBNAIL_MAX_SIZE as f64 / width.max(height).max(1) as f64).min(1.0);
+    let thumb_w = ((width as f64 * scale).round() as u32).max(1);
+    let thumb_h = ((height as f64 * scale).round() as u32).max(1);
+
+    let thumb = imageops::thumbnail(image, thumb_w, thumb_h);
+    thumb.save_with_format(
+        images_dir().join(thumbnail_file_name(file_name)),
+        ImageFormat::Png,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+// src/db/migrations.rs
+//
+// The cloud schema lives in `migrations/*.sql`, applied in order by sqlx and
+// recorded in `_sqlx_migrations`. The files are embedded at build time; the
+// same directory works with `sqlx migrate run` against a local Postgres.
+// Never edit a migration that has shipped (sqlx checks their checksums),
+// add a new file instead.
+use sqlx::PgPool;
+
+pub async fn run_migrations(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut migrator = sqlx::migrate!("./migrations");
+    // Clients update at different times; an older build must keep working
+    // against a schema a newer one has already moved forward
+    migrator.set_ignore_missing(true);
+
+    println!("📝 Applying Postgres migrations...");
+    migrator.run(pool).await?;
+
+    let latest = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+    println!("✅ Postgres schema at version {}", latest);
+    Ok(())
+}
@@ -1,12 +1,24 @@
 // src/db/mod.rs
 pub mod database;
+pub mod migrations;
 pub mod schemas;
 pub mod users_repository;
 pub mod tags_repository;
+pub mod entry_tags_repository;
 pub mod payments_repository;
 pub mod sqlite_database;
+pub mod sqlite_migrations;
 pub mod sqlite_users_repository;
 pub mod sqlite_tags_repository;
+pub mod sqlite_entry_tags_repository;
+pub mod image_store;
+pub mod blob_store;
+pub mod blobs_repository;
+pub mod sqlite_blobs_repository;
+pub mod capture_settings_repository;
+pub mod sqlite_capture_settings_repository;
+pub mod capture_rules_repository;
+pub mod sqlite_capture_rules_repository;
 
 
 pub use database::*;
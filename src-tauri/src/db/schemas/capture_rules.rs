@@ -0,0 +1,178 @@
+// src/db/schemas/capture_rules.rs
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// What a rule looks at. Every condition that is set must match; unset ones
+/// are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RuleConditions {
+    /// Regex over the copied content
+    pub content_pattern: Option<String>,
+    /// Case-insensitive substring of the source app
+    pub source_app: Option<String>,
+    /// Case-insensitive substring of the source window title
+    pub source_window: Option<String>,
+    /// Detected content type, primary or secondary ("json", "url", "image", ...)
+    pub content_type: Option<String>,
+}
+
+/// What a matching rule does to the entry before it is saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RuleActions {
+    pub add_tags: Vec<String>,
+    pub pin: bool,
+    pub mark_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRule {
+    pub id: i64,
+    pub organization_id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub position: i64,
+    pub conditions: RuleConditions,
+    pub actions: RuleActions,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCaptureRule {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub position: i64,
+    pub conditions: RuleConditions,
+    pub actions: RuleActions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UpdateCaptureRule {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    pub position: Option<i64>,
+    pub conditions: Option<RuleConditions>,
+    pub actions: Option<RuleActions>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Local row; conditions and actions are kept as JSON TEXT.
+#[derive(Debug, Clone, FromRow)]
+pub struct LocalCaptureRule {
+    pub id: i64,
+    pub organization_id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub position: i64,
+    pub conditions: String,
+    pub actions: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub sync_status: String,
+    pub server_id: Option<i64>,
+}
+
+impl LocalCaptureRule {
+    pub fn parsed(&self) -> CaptureRule {
+        CaptureRule {
+            id: self.id,
+            organization_id: self.organization_id.clone(),
+            name: self.name.clone(),
+            enabled: self.enabled,
+            position: self.position,
+            conditions: serde_json::from_str(&self.conditions).unwrap_or_default(),
+            actions: serde_json::from_str(&self.actions).unwrap_or_default(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl RuleConditions {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Blank strings count as unset.
+    pub fn normalized(self) -> Self {
+        let clean = |value: Option<String>| {
+            value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+        };
+
+        Self {
+            content_pattern: clean(self.content_pattern),
+            source_app: clean(self.source_app),
+            source_window: clean(self.source_window),
+            content_type: clean(self.content_type).map(|t| t.to_lowercase()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content_pattern.is_none()
+            && self.source_app.is_none()
+            && self.source_window.is_none()
+            && self.content_type.is_none()
+    }
+
+    /// The content pattern compiled, or the error shown to the user.
+    pub fn compile_pattern(&self) -> Result<Option<Regex>, String> {
+        self.content_pattern
+            .as_deref()
+            .map(|pattern| Regex::new(pattern).map_err(|e| format!("Invalid content pattern: {}", e)))
+            .transpose()
+    }
+}
+
+impl RuleActions {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Trims tag names and drops empty or duplicate ones.
+    pub fn normalized(mut self) -> Self {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.add_tags.drain(..) {
+            let tag = tag.trim().to_string();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        self.add_tags = tags;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.add_tags.is_empty() && !self.pin && !self.mark_sensitive
+    }
+}
+
+impl CaptureRule {
+    /// Checks a rule before it is saved; the error is shown to the user.
+    pub fn validate(
+        name: &str,
+        conditions: &RuleConditions,
+        actions: &RuleActions,
+    ) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 100 {
+            return Err("Rule name must be 1-100 characters".to_string());
+        }
+        if conditions.is_empty() {
+            return Err("A rule needs at least one condition".to_string());
+        }
+        if actions.is_empty() {
+            return Err("A rule needs at least one action".to_string());
+        }
+        conditions.compile_pattern()?;
+        Ok(())
+    }
+}
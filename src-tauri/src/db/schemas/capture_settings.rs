@@ -0,0 +1,110 @@
+// src/db/schemas/capture_settings.rs
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::capture::secrets::{SecretKind, SecretPolicy};
+
+/// Per-organization settings that decide what the clipboard monitor records.
+/// Stored as one JSON document so new options don't need a schema change on
+/// either database; missing fields fall back to their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CaptureSettings {
+    /// Copies whose source app or window matches any of these are never saved.
+    pub excluded_apps: Vec<String>,
+    /// When non-empty, only copies from a matching app or window are saved.
+    pub allowed_apps: Vec<String>,
+    /// What to do when a copy looks like a secret (key, token, card number)
+    pub secret_policy: SecretPolicy,
+    /// Per-kind exceptions to `secret_policy`
+    pub secret_policy_overrides: HashMap<SecretKind, SecretPolicy>,
+    /// Lifetime of entries stored under the `expire` policy
+    pub sensitive_expiry_minutes: u32,
+    /// Linux: also record the PRIMARY selection (select to copy, middle-click paste)
+    pub capture_primary: bool,
+    /// How long a PRIMARY selection must stay unchanged before it is
+    /// recorded, so drag-selects in progress are skipped
+    pub primary_debounce_ms: u64,
+    /// Linux: mirror PRIMARY into CLIPBOARD and CLIPBOARD into PRIMARY
+    pub sync_primary_and_clipboard: bool,
+    /// Text copies larger than this go to the blob store; the entry keeps
+    /// a preview
+    pub max_inline_bytes: u64,
+    /// Clipboard changes closer together than this count as one burst and
+    /// only the last value is saved; 0 saves every change
+    pub settle_ms: u64,
+    /// Skip content its owner marked concealed or transient (password
+    /// managers, generated content)
+    pub honor_clipboard_hints: bool,
+}
+
+pub const DEFAULT_MAX_INLINE_BYTES: u64 = 256 * 1024;
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            excluded_apps: Vec::new(),
+            allowed_apps: Vec::new(),
+            secret_policy: SecretPolicy::default(),
+            secret_policy_overrides: HashMap::new(),
+            sensitive_expiry_minutes: 60,
+            capture_primary: false,
+            primary_debounce_ms: 750,
+            sync_primary_and_clipboard: false,
+            max_inline_bytes: DEFAULT_MAX_INLINE_BYTES,
+            settle_ms: 300,
+            honor_clipboard_hints: true,
+        }
+    }
+}
+
+impl CaptureSettings {
+    pub fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).unwrap_or_else(|e| {
+            eprintln!("⚠️ Invalid capture settings JSON, using defaults: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Whether a copy from this app/window may be recorded. Patterns are
+    /// case-insensitive substrings; the denylist wins over the allowlist.
+    pub fn allows_source(&self, source_app: &str, source_window: &str) -> bool {
+        let app = source_app.to_lowercase();
+        let window = source_window.to_lowercase();
+
+        let matches = |pattern: &String| {
+            let pattern = pattern.trim().to_lowercase();
+            !pattern.is_empty() && (app.contains(&pattern) || window.contains(&pattern))
+        };
+
+        if self.excluded_apps.iter().any(matches) {
+            return false;
+        }
+
+        self.allowed_apps.iter().all(|p| p.trim().is_empty()) || self.allowed_apps.iter().any(matches)
+    }
+
+    /// Trims patterns and drops empty or duplicate ones before saving.
+    pub fn normalized(mut self) -> Self {
+        fn clean(list: &mut Vec<String>) {
+            let mut seen = std::collections::HashSet::new();
+            list.retain_mut(|p| {
+                *p = p.trim().to_string();
+                !p.is_empty() && seen.insert(p.to_lowercase())
+            });
+        }
+
+        clean(&mut self.excluded_apps);
+        clean(&mut self.allowed_apps);
+        self.sensitive_expiry_minutes = self.sensitive_expiry_minutes.max(1);
+        self.primary_debounce_ms = self.primary_debounce_ms.clamp(100, 10_000);
+        self.max_inline_bytes = self.max_inline_bytes.clamp(16 * 1024, 16 * 1024 * 1024);
+        self.settle_ms = self.settle_ms.min(2_000);
+        self
+    }
+}
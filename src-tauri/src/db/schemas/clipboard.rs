@@ -1,20 +1,50 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::capture::classifier;
+use crate::search::query::Filter;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ClipboardEntry {
     pub id: i64,
     pub content: String,
-    pub content_type: String,        // "text", "image", "file", etc.
+    pub content_type: String,        // "text", "image", "files", etc.
     pub content_hash: String,        // For deduplication
     pub source_app: String,
     pub source_window: String,
     pub timestamp: DateTime<Utc>,
     pub created_at: DateTime<Utc>,    
-    pub tags: Option<String>,        // JSON array of tags
+    pub tags: Option<String>,        // JSON array of tag names, kept in step with entry_tags
     pub is_pinned: bool,
     pub organization_id: Option<String>,
+    #[sqlx(default)]
+    pub image_width: Option<i64>,
+    #[sqlx(default)]
+    pub image_height: Option<i64>,
+    #[sqlx(default)]
+    pub byte_size: Option<i64>,      // Size of the captured payload (PNG bytes for images)
+    #[sqlx(default)]
+    pub image_path: Option<String>,  // Local PNG file, never synced
+    #[sqlx(default)]
+    pub is_sensitive: bool,          // Looked like a secret; local only, never synced
+    #[sqlx(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[sqlx(default)]
+    pub copy_count: i64,             // How many times this content was copied
+    #[sqlx(default)]
+    pub last_source_app: Option<String>, // App of the most recent copy
+    #[sqlx(default)]
+    #[serde(serialize_with = "crate::capture::html::serialize_sanitized")]
+    pub content_html: Option<String>,    // Rich-text flavor, raw; sanitized when serialized
+    #[sqlx(default)]
+    pub file_list: Option<String>,       // JSON array of FileRef for "files" entries, never synced
+    #[sqlx(default)]
+    pub content_meta: Option<String>,    // Classifier metadata JSON, incl. every matched type
+    #[sqlx(default)]
+    pub blob_ref: Option<String>,        // Hash of the full text in the blob store; `content` is a preview
 }
 
 
@@ -29,7 +59,18 @@ pub struct NewClipboardEntry {
     pub tags: Option<String>,
     pub is_pinned: bool,
     pub organization_id: Option<String>,
-    
+    pub image_width: Option<i64>,
+    pub image_height: Option<i64>,
+    pub byte_size: Option<i64>,
+    pub image_path: Option<String>,
+    pub is_sensitive: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub copy_count: i64,
+    pub last_source_app: Option<String>,
+    pub content_html: Option<String>,
+    pub file_list: Option<String>,
+    pub content_meta: Option<String>,
+    pub blob_ref: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -39,18 +80,140 @@ pub struct UpdateClipboardEntry {
 }
 
 // In src/db/schemas/clipboard.rs
+/// An entry matched by full-text search.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchHit {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub entry: ClipboardEntry,
+    /// Excerpt around the best match, HTML-escaped, matches in `<mark>`
+    pub snippet: String,
+    /// bm25 score; lower is a better match
+    pub rank: f64,
+}
+
+/// Sort order of the history list. Both end with `id` so entries copied in
+/// the same second keep a stable place between pages.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOrder {
+    /// `timestamp DESC, id DESC`
+    #[default]
+    Newest,
+    /// `is_pinned DESC, timestamp DESC, id DESC`
+    PinnedFirst,
+}
+
+/// Which side of the cursor to read.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PageDirection {
+    /// Further down the list, away from the newest entries
+    #[default]
+    Older,
+    /// Back up the list, including copies made since the first page loaded
+    Newer,
+}
+
+/// Sort key of an entry in the history list. Pages start right after (or
+/// before) the cursor's entry, so rows inserted meanwhile don't shift them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HistoryCursor {
+    pub timestamp: DateTime<Utc>,
+    pub id: i64,
+    /// Only compared with `PinnedFirst` ordering
+    #[serde(default)]
+    pub is_pinned: bool,
+}
+
+impl From<&ClipboardEntry> for HistoryCursor {
+    fn from(entry: &ClipboardEntry) -> Self {
+        Self { timestamp: entry.timestamp, id: entry.id, is_pinned: entry.is_pinned }
+    }
+}
+
+/// Optional conditions on a history page, same meaning as the search
+/// operators of the same name.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HistoryFilter {
+    pub tag: Option<String>,
+    pub app: Option<String>,
+    pub content_type: Option<String>,
+    pub pinned: Option<bool>,
+    pub after: Option<NaiveDate>,
+    pub before: Option<NaiveDate>,
+}
+
+impl HistoryFilter {
+    pub fn filters(&self) -> Vec<Filter> {
+        let text = |value: &Option<String>| {
+            value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+        };
+
+        let mut filters = Vec::new();
+        filters.extend(text(&self.tag).map(Filter::Tag));
+        filters.extend(text(&self.app).map(Filter::App));
+        filters.extend(text(&self.content_type).map(|t| Filter::Type(t.to_lowercase())));
+        filters.extend(self.pinned.map(Filter::Pinned));
+        filters.extend(self.after.map(Filter::After));
+        filters.extend(self.before.map(Filter::Before));
+        filters
+    }
+}
+
+/// What the history list shows of an entry. The full row, with all of its
+/// content, is fetched by id when the entry is opened.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EntrySummary {
+    pub id: i64,
+    /// Start of the content, cut on a character boundary
+    pub preview: String,
+    /// `preview` is not the whole content
+    pub is_truncated: bool,
+    pub content_type: String,
+    pub source_app: String,
+    pub source_window: String,
+    pub tags: Option<String>,        // JSON array of tag names, as in ClipboardEntry
+    pub is_pinned: bool,
+    pub is_sensitive: bool,
+    /// Bytes of the full content (PNG bytes for images)
+    pub size: i64,
+    pub copy_count: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&EntrySummary> for HistoryCursor {
+    fn from(summary: &EntrySummary) -> Self {
+        Self { timestamp: summary.timestamp, id: summary.id, is_pinned: summary.is_pinned }
+    }
+}
+
+/// One page of history, newest first whichever direction it was read in.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage<T = ClipboardEntry> {
+    pub entries: Vec<T>,
+    /// Cursor of the last entry, to read `Older` from
+    pub older: Option<HistoryCursor>,
+    /// Cursor of the first entry, to read `Newer` from (also to poll for new copies)
+    pub newer: Option<HistoryCursor>,
+    pub has_older: bool,
+    pub has_newer: bool,
+}
+
 impl NewClipboardEntry {
     pub fn from_monitoring_data(
         content: String,
         source_app: String,
         source_window: String,
     ) -> Self {
-        let content_hash = format!("{:x}", md5::compute(&content));
-        let content_type = detect_content_type(&content);
+        let content_hash = content_hash(&content);
+        let classification = classifier::classify(&content);
+        let byte_size = Some(content.len() as i64);
+        let last_source_app = Some(source_app.clone());
         
         Self {
             content,
-            content_type,
+            content_type: classification.content_type.clone(),
             content_hash,
             source_app,
             source_window,
@@ -58,18 +221,151 @@ impl NewClipboardEntry {
             tags: None,
             is_pinned: false,
             organization_id:None, // Set to None initially
+            image_width: None,
+            image_height: None,
+            byte_size,
+            image_path: None,
+            is_sensitive: false,
+            expires_at: None,
+            copy_count: 1,
+            last_source_app,
+            content_html: None,
+            file_list: None,
+            content_meta: Some(classification.metadata_json()),
+            blob_ref: None,
+        }
+    }
+
+    /// Builds an entry for an image that has already been written to disk.
+    /// `content` holds a short human-readable label so text-only consumers
+    /// (search, cloud sync) still have something meaningful to show.
+    pub fn from_image_data(
+        content_hash: String,
+        width: u32,
+        height: u32,
+        byte_size: u64,
+        image_path: String,
+        source_app: String,
+        source_window: String,
+    ) -> Self {
+        Self {
+            content: format!("Image {}×{}", width, height),
+            content_type: "image".to_string(),
+            content_hash,
+            source_app,
+            source_window,
+            timestamp: Utc::now(),
+            tags: None,
+            is_pinned: false,
+            organization_id: None,
+            image_width: Some(width as i64),
+            image_height: Some(height as i64),
+            byte_size: Some(byte_size as i64),
+            image_path: Some(image_path),
+            is_sensitive: false,
+            expires_at: None,
+            copy_count: 1,
+            last_source_app: None,
+            content_html: None,
+            file_list: None,
+            content_meta: None,
+            blob_ref: None,
         }
     }
+
+    /// Builds an entry for files copied in a file manager. `content` lists
+    /// one path per line so search and cloud sync see something readable.
+    pub fn from_file_list(
+        paths: &[PathBuf],
+        source_app: String,
+        source_window: String,
+    ) -> Self {
+        let files: Vec<FileRef> = paths.iter().map(|p| FileRef::stat(p)).collect();
+        let content = files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        // Prefixed so a file list never dedupes against the same paths copied as text
+        let content_hash = content_hash(&format!("files:{}", content));
+        let byte_size = Some(files.iter().filter_map(|f| f.size).sum::<u64>() as i64);
+
+        Self {
+            content,
+            content_type: "files".to_string(),
+            content_hash,
+            last_source_app: Some(source_app.clone()),
+            source_app,
+            source_window,
+            timestamp: Utc::now(),
+            tags: None,
+            is_pinned: false,
+            organization_id: None,
+            image_width: None,
+            image_height: None,
+            byte_size,
+            image_path: None,
+            is_sensitive: false,
+            expires_at: None,
+            copy_count: 1,
+            content_html: None,
+            file_list: serde_json::to_string(&files).ok(),
+            content_meta: None,
+            blob_ref: None,
+        }
+    }
+}
+
+/// One path of a copied file list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileRef {
+    pub path: String,
+    pub size: Option<u64>, // Bytes; None for directories and missing files
+    pub exists: bool,
 }
 
-fn detect_content_type(content: &str) -> String {
-    if content.starts_with("http://") || content.starts_with("https://") {
-        "url".to_string()
-    } else if content.contains('@') && content.contains('.') {
-        "email".to_string()
-    } else if content.chars().all(|c| c.is_numeric() || c.is_whitespace()) {
-        "numeric".to_string()
-    } else {
-        "text".to_string()
+impl FileRef {
+    /// Looks the path up on disk now.
+    pub fn stat(path: &Path) -> Self {
+        let metadata = std::fs::metadata(path).ok();
+
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            size: metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len()),
+            exists: metadata.is_some(),
+        }
     }
-}
\ No newline at end of file
+}
+
+impl ClipboardEntry {
+    /// Files of a "files" entry as captured. Entries pulled from the cloud
+    /// have no `file_list`, so fall back to the paths in `content`.
+    pub fn files(&self) -> Vec<FileRef> {
+        self.file_list
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_else(|| {
+                self.content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(|line| FileRef { path: line.to_string(), size: None, exists: false })
+                    .collect()
+            })
+    }
+
+    /// Names of the tags linked to this entry.
+    pub fn tag_names(&self) -> Vec<String> {
+        tag_names(self.tags.as_deref())
+    }
+}
+
+/// Parses the JSON array of tag names stored in `clipboard_entries.tags`.
+pub fn tag_names(tags: Option<&str>) -> Vec<String> {
+    tags.and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+/// Hex SHA-256 of the text, used to dedupe entries within an organization.
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
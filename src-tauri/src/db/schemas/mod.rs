@@ -1,6 +1,11 @@
 pub mod clipboard;
 pub mod users;
-pub use clipboard::{ClipboardEntry, NewClipboardEntry, UpdateClipboardEntry};
+pub use clipboard::{
+    ClipboardEntry, EntrySummary, HistoryCursor, HistoryFilter, HistoryOrder, HistoryPage, NewClipboardEntry,
+    PageDirection, SearchHit, UpdateClipboardEntry,
+};
 pub mod tags;
 pub mod payments;
+pub mod capture_settings;
+pub mod capture_rules;
 pub use payments::{Payment, NewPayment, PaymentStatus};
\ No newline at end of file
@@ -0,0 +1,109 @@
+// src/db/sqlite_blobs_repository.rs
+
+use std::collections::HashSet;
+
+use crate::db::blob_store::StoredBlob;
+use sqlx::SqlitePool;
+
+pub struct SqliteBlobsRepository;
+
+impl SqliteBlobsRepository {
+    /// Notes that the organization has an entry backed by this blob.
+    pub async fn record(
+        pool: &SqlitePool,
+        organization_id: &str,
+        blob: &StoredBlob,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_blobs (organization_id, hash, byte_size, stored_size)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(organization_id, hash) DO NOTHING
+            "#,
+        )
+        .bind(organization_id)
+        .bind(&blob.hash)
+        .bind(blob.byte_size as i64)
+        .bind(blob.stored_size as i64)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Blobs not uploaded yet, smallest first. Blobs only used by sensitive
+    /// entries are never returned.
+    pub async fn get_pending_upload(
+        pool: &SqlitePool,
+        organization_id: &str,
+        limit: i64,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT b.hash FROM content_blobs b
+            WHERE b.organization_id = ?1
+              AND b.sync_status = 'local'
+              AND EXISTS (
+                  SELECT 1 FROM clipboard_entries e
+                  WHERE e.organization_id = b.organization_id
+                    AND e.blob_ref = b.hash
+                    AND e.is_sensitive = FALSE
+              )
+            ORDER BY b.stored_size ASC
+            LIMIT ?2
+            "#,
+        )
+        .bind(organization_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(hash,)| hash).collect())
+    }
+
+    pub async fn mark_uploaded(
+        pool: &SqlitePool,
+        organization_id: &str,
+        hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE content_blobs SET sync_status = 'synced' WHERE organization_id = ?1 AND hash = ?2",
+        )
+        .bind(organization_id)
+        .bind(hash)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drops blob rows no entry of their organization points at any more.
+    pub async fn remove_unreferenced(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM content_blobs
+            WHERE NOT EXISTS (
+                SELECT 1 FROM clipboard_entries e
+                WHERE e.organization_id = content_blobs.organization_id
+                  AND e.blob_ref = content_blobs.hash
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Every blob hash some entry still uses, across organizations. Entries
+    /// pulled from the cloud count too, their blob may be downloaded later.
+    pub async fn referenced_hashes(pool: &SqlitePool) -> Result<HashSet<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT blob_ref FROM clipboard_entries WHERE blob_ref IS NOT NULL",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(hash,)| hash).collect())
+    }
+}
@@ -0,0 +1,258 @@
+// src/db/sqlite_capture_rules_repository.rs
+
+use crate::db::schemas::capture_rules::{
+    CaptureRule, LocalCaptureRule, NewCaptureRule, RuleActions, RuleConditions, UpdateCaptureRule,
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+pub struct SqliteCaptureRulesRepository;
+
+const RULE_COLUMNS: &str = "id, organization_id, name, enabled, position, conditions, actions, \
+                            created_at, updated_at, sync_status, server_id";
+
+impl SqliteCaptureRulesRepository {
+    /// Rules of the organization in evaluation order, deleted ones excluded.
+    pub async fn get_for_org(
+        pool: &SqlitePool,
+        organization_id: &str,
+    ) -> Result<Vec<CaptureRule>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "SELECT {} FROM capture_rules
+             WHERE organization_id = ?1 AND sync_status != 'deleted'
+             ORDER BY position ASC, id ASC",
+            RULE_COLUMNS
+        ))
+        .bind(organization_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.iter().map(LocalCaptureRule::parsed).collect())
+    }
+
+    pub async fn get_row(
+        pool: &SqlitePool,
+        rule_id: i64,
+        organization_id: &str,
+    ) -> Result<Option<LocalCaptureRule>, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "SELECT {} FROM capture_rules
+             WHERE id = ?1 AND organization_id = ?2 AND sync_status != 'deleted'",
+            RULE_COLUMNS
+        ))
+        .bind(rule_id)
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn get_by_server_id(
+        pool: &SqlitePool,
+        server_id: i64,
+        organization_id: &str,
+    ) -> Result<Option<LocalCaptureRule>, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "SELECT {} FROM capture_rules WHERE server_id = ?1 AND organization_id = ?2",
+            RULE_COLUMNS
+        ))
+        .bind(server_id)
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &SqlitePool,
+        organization_id: &str,
+        rule: &NewCaptureRule,
+    ) -> Result<LocalCaptureRule, sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "INSERT INTO capture_rules
+                (organization_id, name, enabled, position, conditions, actions, created_at, updated_at, sync_status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7, 'local')
+             RETURNING {}",
+            RULE_COLUMNS
+        ))
+        .bind(organization_id)
+        .bind(rule.name.trim())
+        .bind(rule.enabled)
+        .bind(rule.position)
+        .bind(rule.conditions.to_json())
+        .bind(rule.actions.to_json())
+        .bind(now)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Applies the set fields and marks the rule for the next cloud sync.
+    pub async fn update(
+        pool: &SqlitePool,
+        rule_id: i64,
+        organization_id: &str,
+        updates: &UpdateCaptureRule,
+    ) -> Result<Option<LocalCaptureRule>, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "UPDATE capture_rules SET
+                name        = COALESCE(?1, name),
+                enabled     = COALESCE(?2, enabled),
+                position    = COALESCE(?3, position),
+                conditions  = COALESCE(?4, conditions),
+                actions     = COALESCE(?5, actions),
+                updated_at  = ?6,
+                sync_status = 'local'
+             WHERE id = ?7 AND organization_id = ?8 AND sync_status != 'deleted'
+             RETURNING {}",
+            RULE_COLUMNS
+        ))
+        .bind(updates.name.as_deref().map(str::trim))
+        .bind(updates.enabled)
+        .bind(updates.position)
+        .bind(updates.conditions.as_ref().map(RuleConditions::to_json))
+        .bind(updates.actions.as_ref().map(RuleActions::to_json))
+        .bind(Utc::now())
+        .bind(rule_id)
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Rules never pushed are removed right away. Synced ones stay behind
+    /// as 'deleted' until the cloud copy is gone too, so a bootstrap doesn't
+    /// bring them back.
+    pub async fn delete(
+        pool: &SqlitePool,
+        rule_id: i64,
+        organization_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let removed = sqlx::query(
+            "DELETE FROM capture_rules WHERE id = ?1 AND organization_id = ?2 AND server_id IS NULL",
+        )
+        .bind(rule_id)
+        .bind(organization_id)
+        .execute(pool)
+        .await?;
+
+        if removed.rows_affected() > 0 {
+            return Ok(true);
+        }
+
+        let marked = sqlx::query(
+            "UPDATE capture_rules SET sync_status = 'deleted', updated_at = ?1
+             WHERE id = ?2 AND organization_id = ?3 AND sync_status != 'deleted'",
+        )
+        .bind(Utc::now())
+        .bind(rule_id)
+        .bind(organization_id)
+        .execute(pool)
+        .await?;
+
+        Ok(marked.rows_affected() > 0)
+    }
+
+    /// Drops the row for good, once the cloud no longer has it.
+    pub async fn purge(pool: &SqlitePool, rule_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM capture_rules WHERE id = ?1")
+            .bind(rule_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Rules created, edited or deleted since the last sync.
+    pub async fn get_pending_sync_for_org(
+        pool: &SqlitePool,
+        organization_id: &str,
+    ) -> Result<Vec<LocalCaptureRule>, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureRule>(&format!(
+            "SELECT {} FROM capture_rules
+             WHERE organization_id = ?1 AND sync_status IN ('local', 'deleted')
+             ORDER BY id ASC",
+            RULE_COLUMNS
+        ))
+        .bind(organization_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_as_synced(
+        pool: &SqlitePool,
+        rule_id: i64,
+        server_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE capture_rules SET sync_status = 'synced', server_id = ?1 WHERE id = ?2")
+            .bind(server_id)
+            .bind(rule_id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Inserts or overwrites the local copy of a cloud rule (bootstrap).
+    pub async fn upsert_from_remote(
+        pool: &SqlitePool,
+        remote: &CaptureRule,
+    ) -> Result<(), sqlx::Error> {
+        let existing = Self::get_by_server_id(pool, remote.id, &remote.organization_id).await?;
+
+        let query = match existing {
+            Some(local) => sqlx::query(
+                "UPDATE capture_rules SET
+                    name = ?1, enabled = ?2, position = ?3, conditions = ?4, actions = ?5,
+                    created_at = ?6, updated_at = ?7, sync_status = 'synced'
+                 WHERE id = ?8",
+            )
+            .bind(&remote.name)
+            .bind(remote.enabled)
+            .bind(remote.position)
+            .bind(remote.conditions.to_json())
+            .bind(remote.actions.to_json())
+            .bind(remote.created_at)
+            .bind(remote.updated_at)
+            .bind(local.id),
+            None => sqlx::query(
+                "INSERT INTO capture_rules
+                    (organization_id, name, enabled, position, conditions, actions,
+                     created_at, updated_at, sync_status, server_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'synced', ?9)",
+            )
+            .bind(&remote.organization_id)
+            .bind(&remote.name)
+            .bind(remote.enabled)
+            .bind(remote.position)
+            .bind(remote.conditions.to_json())
+            .bind(remote.actions.to_json())
+            .bind(remote.created_at)
+            .bind(remote.updated_at)
+            .bind(remote.id),
+        };
+
+        query.execute(pool).await?;
+        Ok(())
+    }
+
+    /// Removes synced rules the cloud no longer has (deleted on another device).
+    pub async fn delete_synced_missing_from(
+        pool: &SqlitePool,
+        organization_id: &str,
+        server_ids: &[i64],
+    ) -> Result<u64, sqlx::Error> {
+        let server_ids = serde_json::to_string(server_ids).unwrap_or_else(|_| "[]".to_string());
+
+        let result = sqlx::query(
+            "DELETE FROM capture_rules
+             WHERE organization_id = ?1
+               AND sync_status = 'synced'
+               AND server_id NOT IN (SELECT value FROM json_each(?2))",
+        )
+        .bind(organization_id)
+        .bind(server_ids)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
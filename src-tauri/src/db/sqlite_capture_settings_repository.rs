@@ -0,0 +1,112 @@
+// src/db/sqlite_capture_settings_repository.rs
+
+use crate::db::schemas::capture_settings::CaptureSettings;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, SqlitePool};
+
+pub struct SqliteCaptureSettingsRepository;
+
+/// Local row, settings kept as JSON TEXT.
+#[derive(Debug, Clone, FromRow)]
+pub struct LocalCaptureSettings {
+    pub settings: String,
+    pub sync_status: String,
+}
+
+impl LocalCaptureSettings {
+    pub fn parsed(&self) -> CaptureSettings {
+        CaptureSettings::from_json(&self.settings)
+    }
+}
+
+impl SqliteCaptureSettingsRepository {
+    pub async fn get_row(
+        pool: &SqlitePool,
+        organization_id: &str,
+    ) -> Result<Option<LocalCaptureSettings>, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureSettings>(
+            r#"
+            SELECT settings, sync_status
+            FROM capture_settings
+            WHERE organization_id = ?1
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// Settings for the organization, defaults if none were saved yet.
+    pub async fn get_for_org(
+        pool: &SqlitePool,
+        organization_id: &str,
+    ) -> Result<CaptureSettings, sqlx::Error> {
+        Ok(Self::get_row(pool, organization_id)
+            .await?
+            .map(|row| row.parsed())
+            .unwrap_or_default())
+    }
+
+    /// Saves a local change and marks it for the next cloud sync.
+    pub async fn save_for_org(
+        pool: &SqlitePool,
+        organization_id: &str,
+        settings: &CaptureSettings,
+    ) -> Result<LocalCaptureSettings, sqlx::Error> {
+        sqlx::query_as::<_, LocalCaptureSettings>(
+            r#"
+            INSERT INTO capture_settings (organization_id, settings, updated_at, sync_status)
+            VALUES (?1, ?2, CURRENT_TIMESTAMP, 'local')
+            ON CONFLICT(organization_id) DO UPDATE SET
+                settings = excluded.settings,
+                updated_at = CURRENT_TIMESTAMP,
+                sync_status = 'local'
+            RETURNING settings, sync_status
+            "#,
+        )
+        .bind(organization_id)
+        .bind(settings.to_json())
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Overwrites the local row with the cloud copy (bootstrap).
+    pub async fn replace_from_remote(
+        pool: &SqlitePool,
+        organization_id: &str,
+        settings: &CaptureSettings,
+        updated_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO capture_settings (organization_id, settings, updated_at, sync_status)
+            VALUES (?1, ?2, ?3, 'synced')
+            ON CONFLICT(organization_id) DO UPDATE SET
+                settings = excluded.settings,
+                updated_at = excluded.updated_at,
+                sync_status = 'synced'
+            "#,
+        )
+        .bind(organization_id)
+        .bind(settings.to_json())
+        .bind(updated_at)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_as_synced(
+        pool: &SqlitePool,
+        organization_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE capture_settings SET sync_status = 'synced' WHERE organization_id = ?1",
+        )
+        .bind(organization_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
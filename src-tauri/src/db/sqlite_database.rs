@@ -1,10 +1,21 @@
 // src/db/sqlite_database.rs
-use sqlx::{sqlite::{SqlitePool, SqlitePoolOptions}};
+use sqlx::{sqlite::{SqlitePool, SqlitePoolOptions, SqliteRow}, FromRow, QueryBuilder, Sqlite, SqliteConnection};
 use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{PathBuf};
 // Reuse your existing schemas from database.rs
-use crate::db::schemas::{ClipboardEntry, NewClipboardEntry, UpdateClipboardEntry};
+use crate::db::schemas::{
+    ClipboardEntry, EntrySummary, HistoryCursor, HistoryFilter, HistoryOrder, HistoryPage, NewClipboardEntry,
+    PageDirection, SearchHit, UpdateClipboardEntry,
+};
+use crate::db::schemas::clipboard::{content_hash, tag_names};
+use crate::capture::classifier;
+use crate::db::blob_store;
+use crate::db::sqlite_entry_tags_repository::SqliteEntryTagsRepository;
+use crate::db::sqlite_migrations;
+use crate::search::fts;
+use crate::search::query::{self, Filter, SearchQuery};
 use log::{info, error};
 use directories::ProjectDirs;
 
@@ -16,7 +27,16 @@ fn to_sqlite_ts(dt: DateTime<Utc>) -> String {
 }
 
 
-fn get_database_path() -> PathBuf {
+/// Directory that holds the SQLite database and everything stored next to it
+/// (captured images, thumbnails). Falls back to the current directory.
+pub fn get_data_dir() -> PathBuf {
+    // Tests never touch the real data (legacy imports, images, blobs)
+    if cfg!(test) {
+        let dir = std::env::temp_dir().join(format!("cliptray-test-{}", std::process::id()));
+        let _ = fs::create_dir_all(&dir);
+        return dir;
+    }
+
     if let Some(proj_dirs) = ProjectDirs::from("com", "ClipTray", "ClipTray") {
         let data_dir = proj_dirs.data_dir();
         
@@ -24,24 +44,28 @@ fn get_database_path() -> PathBuf {
             if let Err(e) = std::fs::create_dir_all(data_dir) {
                 eprintln!("Warning: Failed to create data directory: {}", e);
                 return std::env::current_dir()
-                    .unwrap_or_else(|_| PathBuf::from("."))
-                    .join("cliptray_offline3.db");
+                    .unwrap_or_else(|_| PathBuf::from("."));
             }
         }
         
-        let db_path = data_dir.join("cliptray_offline3.db");
-        info!("Resolved database path: {:?}", db_path);
-        db_path
+        data_dir.to_path_buf()
     } else {
         // Fallback to current directory
-        let current_dir = std::env::current_dir()
-            .unwrap_or_else(|_| PathBuf::from("."));
-        let db_path = current_dir.join("cliptray_offline3.db");
-        info!("Using fallback database path: {:?}", db_path);
-        db_path
+        std::env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
     }
 }
 
+/// File name of the live database. Schema changes are migrations (see
+/// sqlite_migrations), not new file names.
+pub const DATABASE_FILE_NAME: &str = "cliptray_offline3.db";
+
+fn get_database_path() -> PathBuf {
+    let db_path = get_data_dir().join(DATABASE_FILE_NAME);
+    info!("Resolved database path: {:?}", db_path);
+    db_path
+}
+
 pub async fn create_sqlite_pool() -> Result<SqlitePool, Box<dyn std::error::Error>> {
     // Get the database path
     let db_path = get_database_path();
@@ -65,168 +89,256 @@ pub async fn create_sqlite_pool() -> Result<SqlitePool, Box<dyn std::error::Erro
     // Log the successful connection
     info!("SQLite database connected successfully!");
 
-    // Create tables if they don't exist
-    create_sqlite_tables(&pool).await?;
+    // Create tables or bring an existing database up to date
+    sqlite_migrations::run_migrations(&pool).await?;
 
     Ok(pool)
 }
 
+/// Empty database at the latest schema that lives as long as the pool.
+#[cfg(test)]
+pub async fn memory_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory SQLite");
+    sqlite_migrations::run_migrations(&pool).await.expect("SQLite migrations");
+    pool
+}
 
+/// Adds the SQL condition for one search operator. Words and phrases are
+/// handled by the FTS5 match instead, unless negated.
+fn push_filter(builder: &mut QueryBuilder<'_, Sqlite>, filter: &Filter) {
+    if !matches!(filter, Filter::Word(_) | Filter::Phrase(_)) {
+        builder.push(" AND ");
+        push_condition(builder, filter);
+    }
+}
 
+/// The parenthesized condition an entry `e` must meet for `filter`.
+fn push_condition(builder: &mut QueryBuilder<'_, Sqlite>, filter: &Filter) {
+    match filter {
+        Filter::Word(word) => push_text_match(builder, fts::word_term(word)),
+        Filter::Phrase(phrase) => push_text_match(builder, fts::phrase_term(phrase)),
+        Filter::Not(inner) => {
+            builder.push("(NOT ");
+            push_condition(builder, inner);
+            builder.push(")");
+        }
+        Filter::Tag(name) => {
+            builder
+                .push("(EXISTS (SELECT 1 FROM entry_tags et JOIN tags t ON t.id = et.tag_id")
+                .push(" WHERE et.entry_id = e.id AND LOWER(t.name) = LOWER(")
+                .push_bind(name.clone())
+                .push(")))");
+        }
+        Filter::App(app) => {
+            let pattern = format!(
+                "%{}%",
+                app.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+            );
+            builder
+                .push("(LOWER(e.source_app) LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR LOWER(COALESCE(e.last_source_app, '')) LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        Filter::Type(content_type) => {
+            builder
+                .push("(e.content_type = ")
+                .push_bind(content_type.clone())
+                .push(" OR EXISTS (SELECT 1 FROM json_each(e.content_meta, '$.types') WHERE json_each.value = ")
+                .push_bind(content_type.clone())
+                .push("))");
+        }
+        Filter::Pinned(pinned) => {
+            builder.push("(e.is_pinned = ").push_bind(*pinned).push(")");
+        }
+        Filter::After(date) => {
+            builder
+                .push("(e.timestamp >= ")
+                .push_bind(to_sqlite_ts(query::start_of_day(*date)))
+                .push(")");
+        }
+        Filter::Before(date) => {
+            builder
+                .push("(e.timestamp < ")
+                .push_bind(to_sqlite_ts(query::start_of_day(*date)))
+                .push(")");
+        }
+    }
+}
 
-pub async fn create_sqlite_tables(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
-    println!("📝 Creating SQLite database tables if they don't exist...");
+/// Entries whose FTS5 row matches `term`; nothing when the text had
+/// nothing searchable in it.
+fn push_text_match(builder: &mut QueryBuilder<'_, Sqlite>, term: Option<String>) {
+    match term {
+        Some(term) => {
+            builder
+                .push("(e.id IN (SELECT rowid FROM clipboard_search WHERE clipboard_search MATCH ")
+                .push_bind(term)
+                .push("))");
+        }
+        None => {
+            builder.push("(0)");
+        }
+    }
+}
 
-    println!("📝 Creating Users table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            organization_id TEXT NOT NULL,
-            firebase_uid TEXT UNIQUE NOT NULL,
-            email TEXT NOT NULL,
-            display_name TEXT,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            purge_cadence TEXT NOT NULL DEFAULT 'never',
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            last_login_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            retain_tags BOOLEAN NOT NULL DEFAULT FALSE,
-            plan TEXT NOT NULL DEFAULT 'free'
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
+/// Characters of content in an `EntrySummary` preview.
+const SUMMARY_PREVIEW_CHARS: i64 = 200;
 
-    
+/// History rows after (or before) the cursor, one more than `limit` to tell
+/// whether there is another page.
+fn history_query(
+    columns: &str,
+    organization_id: &str,
+    order: HistoryOrder,
+    direction: PageDirection,
+    cursor: Option<&HistoryCursor>,
+    filter: &HistoryFilter,
+    limit: i64,
+) -> QueryBuilder<'static, Sqlite> {
+    let pinned_first = order == HistoryOrder::PinnedFirst;
+    let older = direction == PageDirection::Older;
 
-    println!("📝 Creating clipboard table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS clipboard_entries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            organization_id TEXT,
-            content TEXT NOT NULL,
-            content_type TEXT NOT NULL DEFAULT 'text',
-            content_hash TEXT UNIQUE NOT NULL,
-            source_app TEXT NOT NULL,
-            source_window TEXT NOT NULL,
-            timestamp DATETIME NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            tags TEXT,
-            is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
-            sync_status TEXT NOT NULL DEFAULT 'local',
-            server_id TEXT
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
+    let mut builder = QueryBuilder::<Sqlite>::new(format!(
+        "SELECT {} FROM clipboard_entries e WHERE e.organization_id = ",
+        columns
+    ));
+    builder.push_bind(organization_id.to_string());
 
-    println!("📝 Creating Tags table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS tags (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            organization_id TEXT NOT NULL,
-            name TEXT NOT NULL,
-            color TEXT NOT NULL DEFAULT '#6B7280',
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            sync_status TEXT NOT NULL DEFAULT 'local',
-        server_id INTEGER
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
+    for filter in filter.filters() {
+        push_filter(&mut builder, &filter);
+    }
 
-    println!("📝 Creating Payments table if not exists...");
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS payments (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            stripe_session_id TEXT NOT NULL,
-            stripe_payment_intent_id TEXT,
-            organization_id TEXT NOT NULL,
-            firebase_uid TEXT NOT NULL,
-            email TEXT NOT NULL,
-            amount_paid INTEGER NOT NULL DEFAULT 0,
-            currency TEXT NOT NULL DEFAULT 'usd',
-            payment_status TEXT NOT NULL DEFAULT 'unpaid' CHECK(payment_status IN ('paid', 'unpaid', 'failed')),
-            plan_type TEXT NOT NULL DEFAULT 'lifetime',
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            paid_at DATETIME,
-            metadata TEXT
-        )
-        "#
-    )
-    .execute(pool)
-    .await?;
+    // Every key sorts descending, so "further down the list" is a plain
+    // row value comparison
+    if let Some(cursor) = cursor {
+        builder
+            .push(if pinned_first { " AND (e.is_pinned, e.timestamp, e.id) " } else { " AND (e.timestamp, e.id) " })
+            .push(if older { "< (" } else { "> (" });
+        if pinned_first {
+            builder.push_bind(cursor.is_pinned).push(", ");
+        }
+        builder
+            .push_bind(to_sqlite_ts(cursor.timestamp))
+            .push(", ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
 
-    // === Indexes ===
-    println!("📝 Creating indexes if not exist...");
-    
-    // Users indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_firebase_uid ON users(firebase_uid)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_organization_id ON users(organization_id)")
-        .execute(pool).await?;
-
-    // Clipboard indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_content_hash ON clipboard_entries(content_hash)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_created_at ON clipboard_entries(created_at DESC)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_source_app ON clipboard_entries(source_app)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_organization_id ON clipboard_entries(organization_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_sync_status ON clipboard_entries(sync_status)")
-        .execute(pool).await?;
-
-    // Tags indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_organization_id ON tags(organization_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name)")
-        .execute(pool).await?;
-    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_organization_name_unique ON tags(organization_id, name)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_sync_status ON tags(sync_status)")
-    .execute(pool).await?;
-
-    // Payments indexes
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_firebase_uid ON payments(firebase_uid)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_organization_id ON payments(organization_id)")
-        .execute(pool).await?;
-    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_stripe_session_id ON payments(stripe_session_id)")
-        .execute(pool).await?;
-    
-    println!("✅ SQLite database tables ready!");
-    Ok(())
+    // Newer pages are read upwards from the cursor, then flipped back
+    let sort = if older { "DESC" } else { "ASC" };
+    builder.push(" ORDER BY ");
+    if pinned_first {
+        builder.push(format!("e.is_pinned {}, ", sort));
+    }
+    builder
+        .push(format!("e.timestamp {0}, e.id {0}", sort))
+        .push(" LIMIT ")
+        .push_bind(limit + 1);
+
+    builder
+}
+
+async fn fetch_history_page<T>(
+    pool: &SqlitePool,
+    mut builder: QueryBuilder<'_, Sqlite>,
+    direction: PageDirection,
+    has_cursor: bool,
+    limit: i64,
+) -> Result<HistoryPage<T>, sqlx::Error>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    for<'a> &'a T: Into<HistoryCursor>,
+{
+    let older = direction == PageDirection::Older;
+
+    let mut entries = builder.build_query_as::<T>().fetch_all(pool).await?;
+    let has_more = entries.len() as i64 > limit;
+    entries.truncate(limit as usize);
+    if !older {
+        entries.reverse();
+    }
+
+    // The cursor's entry sits on the other side, so there is more there
+    let (has_older, has_newer) = if older {
+        (has_more, has_cursor)
+    } else {
+        (has_cursor, has_more)
+    };
+
+    Ok(HistoryPage {
+        older: entries.last().map(Into::into),
+        newer: entries.first().map(Into::into),
+        entries,
+        has_older,
+        has_newer,
+    })
+}
+
+/// An edit would give the entry the same content as another entry of its
+/// organization, which dedupe allows only once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateEntry {
+    pub existing_id: i64,
+}
+
+impl std::fmt::Display for DuplicateEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Another entry (#{}) already has this content", self.existing_id)
+    }
 }
 
-// Import the helper functions from your existing database.rs
-use crate::db::database::{tags_to_json, json_to_tags};
+impl std::error::Error for DuplicateEntry {}
 
 // SQLite Clipboard operations
 pub struct SqliteClipboardRepository;
 
 impl SqliteClipboardRepository {
     
+    /// Inserts a captured entry. Copying content that is already stored
+    /// bumps the existing row instead: newer timestamp, higher copy_count,
+    /// latest source app. Existing tags are kept and tags from capture rules
+    /// are added to them; rules can pin or flag a row, never undo that. A
+    /// pinned row never gets an expiry from a sensitive re-copy.
     pub async fn save_entry(
         pool: &SqlitePool,
         entry: NewClipboardEntry,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        sqlx::query(
+    ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
+        let organization_id = entry.organization_id.clone().unwrap_or_default();
+        let rule_tags = tag_names(entry.tags.as_deref());
+
+        let mut tx = pool.begin().await?;
+        let mut result = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             INSERT INTO clipboard_entries 
-            (content, content_type, content_hash, source_app, source_window, timestamp, tags, organization_id, sync_status)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'local')
+            (content, content_type, content_hash, source_app, source_window, timestamp, organization_id, sync_status,
+             image_width, image_height, byte_size, image_path, is_sensitive, expires_at, copy_count, last_source_app,
+             content_html, file_list, content_meta, is_pinned, blob_ref)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'local', ?8, ?9, ?10, ?11, ?12, ?13, 1, ?4, ?14, ?15, ?16, ?17, ?18)
+            ON CONFLICT(organization_id, content_hash) DO UPDATE SET
+                timestamp       = excluded.timestamp,
+                content_html    = excluded.content_html,
+                file_list       = excluded.file_list,
+                content_meta    = excluded.content_meta,
+                content         = excluded.content,
+                blob_ref        = excluded.blob_ref,
+                copy_count      = clipboard_entries.copy_count + 1,
+                last_source_app = excluded.source_app,
+                is_pinned       = clipboard_entries.is_pinned OR excluded.is_pinned,
+                is_sensitive    = clipboard_entries.is_sensitive OR excluded.is_sensitive,
+                expires_at      = CASE WHEN excluded.is_sensitive
+                                            AND NOT (clipboard_entries.is_pinned OR excluded.is_pinned)
+                                       THEN excluded.expires_at
+                                       ELSE clipboard_entries.expires_at END,
+                sync_status     = 'local'
+            RETURNING *
             "#,
         )
         .bind(entry.content)
@@ -235,13 +347,46 @@ impl SqliteClipboardRepository {
         .bind(entry.source_app)
         .bind(entry.source_window)
         .bind(to_sqlite_ts(entry.timestamp))
-        .bind(entry.tags)
         .bind(entry.organization_id)
+        .bind(entry.image_width)
+        .bind(entry.image_height)
+        .bind(entry.byte_size)
+        .bind(entry.image_path)
+        .bind(entry.is_sensitive)
+        .bind(entry.expires_at.map(to_sqlite_ts))
+        .bind(entry.content_html)
+        .bind(entry.file_list)
+        .bind(entry.content_meta)
+        .bind(entry.is_pinned)
+        .bind(entry.blob_ref)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !rule_tags.is_empty() {
+            SqliteEntryTagsRepository::link_names(&mut tx, result.id, &organization_id, &rule_tags).await?;
+            result = sqlx::query_as::<_, ClipboardEntry>("SELECT * FROM clipboard_entries WHERE id = ?1")
+                .bind(result.id)
+                .fetch_one(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Deletes sensitive entries whose expiry has passed. Pinned entries
+    /// are kept.
+    pub async fn delete_expired_entries(
+        pool: &SqlitePool,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let result = sqlx::query(
+            "DELETE FROM clipboard_entries WHERE expires_at IS NOT NULL AND expires_at <= ?1 AND is_pinned = 0",
+        )
+        .bind(to_sqlite_ts(Utc::now()))
         .execute(pool)
         .await?;
 
-        Ok(())
-    
+        Ok(result.rows_affected())
     }
 
      pub async fn get_by_server_id(
@@ -268,7 +413,8 @@ impl SqliteClipboardRepository {
         pool: &SqlitePool,
         remote: &ClipboardEntry,
     ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-        let result = sqlx::query_as::<_, ClipboardEntry>(
+        let mut tx = pool.begin().await?;
+        let inserted = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             INSERT INTO clipboard_entries (
                 organization_id,
@@ -278,12 +424,19 @@ impl SqliteClipboardRepository {
                 source_app,
                 source_window,
                 timestamp,
-                tags,
                 is_pinned,
                 sync_status,
-                server_id
+                server_id,
+                image_width,
+                image_height,
+                byte_size,
+                copy_count,
+                last_source_app,
+                content_html,
+                content_meta,
+                blob_ref
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'synced', ?10)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'synced', ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
             RETURNING *
             "#
         )
@@ -294,12 +447,22 @@ impl SqliteClipboardRepository {
         .bind(&remote.source_app)
         .bind(&remote.source_window)
         .bind(to_sqlite_ts(remote.timestamp))
-        .bind(&remote.tags)
         .bind(remote.is_pinned)
         .bind(remote.id.to_string())
-        .fetch_one(pool)
+        .bind(remote.image_width)
+        .bind(remote.image_height)
+        .bind(remote.byte_size)
+        .bind(remote.copy_count.max(1))
+        .bind(&remote.last_source_app)
+        .bind(&remote.content_html)
+        .bind(&remote.content_meta)
+        .bind(&remote.blob_ref)
+        .fetch_one(&mut *tx)
         .await?;
 
+        let result = Self::replace_remote_tags(&mut tx, inserted, remote).await?;
+        tx.commit().await?;
+
         Ok(result)
     }
 
@@ -309,7 +472,8 @@ impl SqliteClipboardRepository {
         local_id: i64,
         remote: &ClipboardEntry,
     ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-        let result = sqlx::query_as::<_, ClipboardEntry>(
+        let mut tx = pool.begin().await?;
+        let updated = sqlx::query_as::<_, ClipboardEntry>(
             r#"
             UPDATE clipboard_entries
             SET
@@ -319,10 +483,14 @@ impl SqliteClipboardRepository {
                 source_app   = ?4,
                 source_window= ?5,
                 timestamp    = ?6,
-                tags         = ?7,
-                is_pinned    = ?8,
+                is_pinned    = ?7,
+                copy_count   = MAX(copy_count, ?9),
+                last_source_app = COALESCE(?10, last_source_app),
+                content_html = ?11,
+                content_meta = ?12,
+                blob_ref     = ?13,
                 sync_status  = 'synced'
-            WHERE id = ?9
+            WHERE id = ?8
             RETURNING *
             "#
         )
@@ -332,12 +500,36 @@ impl SqliteClipboardRepository {
         .bind(&remote.source_app)
         .bind(&remote.source_window)
         .bind(to_sqlite_ts(remote.timestamp))
-        .bind(&remote.tags)
         .bind(remote.is_pinned)
         .bind(local_id)
-        .fetch_one(pool)
+        .bind(remote.copy_count)
+        .bind(&remote.last_source_app)
+        .bind(&remote.content_html)
+        .bind(&remote.content_meta)
+        .bind(&remote.blob_ref)
+        .fetch_one(&mut *tx)
         .await?;
 
+        let result = Self::replace_remote_tags(&mut tx, updated, remote).await?;
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Links the local row to the remote entry's tags, by name.
+    async fn replace_remote_tags(
+        conn: &mut SqliteConnection,
+        local: ClipboardEntry,
+        remote: &ClipboardEntry,
+    ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
+        let organization_id = local.organization_id.clone().unwrap_or_default();
+        SqliteEntryTagsRepository::replace_names(conn, local.id, &organization_id, &remote.tag_names()).await?;
+
+        let result = sqlx::query_as::<_, ClipboardEntry>("SELECT * FROM clipboard_entries WHERE id = ?1")
+            .bind(local.id)
+            .fetch_one(&mut *conn)
+            .await?;
+
         Ok(result)
     }
 
@@ -351,7 +543,7 @@ impl SqliteClipboardRepository {
         let limit = limit.unwrap_or(100);
         
         let results = sqlx::query_as::<_, ClipboardEntry>(
-            "SELECT * FROM clipboard_entries WHERE organization_id = ?1 ORDER BY created_at DESC LIMIT ?2"
+            "SELECT * FROM clipboard_entries WHERE organization_id = ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2"
         )
         .bind(organization_id)
         .bind(limit)
@@ -360,21 +552,96 @@ impl SqliteClipboardRepository {
         
         Ok(results)
     }
+
+    /// One page of the organization's history, in keyset order from the
+    /// cursor (from the top of the list without one). Reading `Newer` from
+    /// the first entry of the top page returns what was copied since.
+    pub async fn get_history_page(
+        pool: &SqlitePool,
+        organization_id: &str,
+        order: HistoryOrder,
+        direction: PageDirection,
+        cursor: Option<&HistoryCursor>,
+        filter: &HistoryFilter,
+        limit: Option<i64>,
+    ) -> Result<HistoryPage, Box<dyn std::error::Error>> {
+        let limit = limit.unwrap_or(100).clamp(1, 1000);
+        let builder = history_query("e.*", organization_id, order, direction, cursor, filter, limit);
+
+        Ok(fetch_history_page(pool, builder, direction, cursor.is_some(), limit).await?)
+    }
+
+    /// Same page as `get_history_page`, but only what the list shows of each
+    /// entry: a preview instead of the content, and none of the large
+    /// columns (HTML, file lists, classifier metadata).
+    pub async fn get_history_summaries(
+        pool: &SqlitePool,
+        organization_id: &str,
+        order: HistoryOrder,
+        direction: PageDirection,
+        cursor: Option<&HistoryCursor>,
+        filter: &HistoryFilter,
+        limit: Option<i64>,
+    ) -> Result<HistoryPage<EntrySummary>, Box<dyn std::error::Error>> {
+        let limit = limit.unwrap_or(200).clamp(1, 5000);
+        let columns = format!(
+            r#"
+            e.id, substr(e.content, 1, {preview}) AS preview,
+            (e.blob_ref IS NOT NULL OR length(e.content) > {preview}) AS is_truncated,
+            e.content_type, e.source_app, e.source_window, e.tags, e.is_pinned, e.is_sensitive,
+            COALESCE(
+                e.byte_size,
+                (SELECT b.byte_size FROM content_blobs b WHERE b.organization_id = e.organization_id AND b.hash = e.blob_ref),
+                length(CAST(e.content AS BLOB))
+            ) AS size,
+            e.copy_count, e.timestamp
+            "#,
+            preview = SUMMARY_PREVIEW_CHARS,
+        );
+        let builder = history_query(&columns, organization_id, order, direction, cursor, filter, limit);
+
+        Ok(fetch_history_page(pool, builder, direction, cursor.is_some(), limit).await?)
+    }
     
-    pub async fn get_by_id(
-        pool: &SqlitePool, 
-        id: i64
+    /// Entry `id` when it belongs to the organization.
+    pub async fn get_by_id_for_org(
+        pool: &SqlitePool,
+        id: i64,
+        organization_id: &str,
     ) -> Result<Option<ClipboardEntry>, Box<dyn std::error::Error>> {
         let result = sqlx::query_as::<_, ClipboardEntry>(
-            "SELECT * FROM clipboard_entries WHERE id = ?1"
+            "SELECT * FROM clipboard_entries WHERE id = ?1 AND organization_id = ?2"
         )
         .bind(id)
+        .bind(organization_id)
         .fetch_optional(pool)
         .await?;
-        
+
         Ok(result)
     }
-    
+
+    /// The organization's entries among `ids`, in no particular order.
+    /// Ids that don't exist or belong elsewhere are left out.
+    pub async fn get_by_ids(
+        pool: &SqlitePool,
+        organization_id: &str,
+        ids: &[i64],
+    ) -> Result<Vec<ClipboardEntry>, Box<dyn std::error::Error>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT * FROM clipboard_entries WHERE organization_id = ");
+        builder.push_bind(organization_id).push(" AND id IN (");
+        let mut separated = builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        separated.push_unseparated(")");
+
+        Ok(builder.build_query_as::<ClipboardEntry>().fetch_all(pool).await?)
+    }
+
     pub async fn get_all(
         pool: &SqlitePool, 
         limit: Option<i64>
@@ -382,7 +649,7 @@ impl SqliteClipboardRepository {
         let limit = limit.unwrap_or(100);
         
         let results = sqlx::query_as::<_, ClipboardEntry>(
-            "SELECT * FROM clipboard_entries ORDER BY created_at DESC LIMIT ?1"
+            "SELECT * FROM clipboard_entries ORDER BY timestamp DESC LIMIT ?1"
         )
         .bind(limit)
         .fetch_all(pool)
@@ -396,7 +663,7 @@ impl SqliteClipboardRepository {
         hours: i32
     ) -> Result<Vec<ClipboardEntry>, Box<dyn std::error::Error>> {
         let results = sqlx::query_as::<_, ClipboardEntry>(
-            "SELECT * FROM clipboard_entries WHERE created_at > datetime('now', ?1) ORDER BY created_at DESC"
+            "SELECT * FROM clipboard_entries WHERE created_at > datetime('now', ?1) ORDER BY timestamp DESC"
         )
         .bind(format!("-{} hours", hours))
         .fetch_all(pool)
@@ -405,43 +672,129 @@ impl SqliteClipboardRepository {
         Ok(results)
     }
     
-    pub async fn search_content(
-        pool: &SqlitePool, 
-        query: &str
+    /// Runs a parsed search box query, scoped to the organization. With
+    /// words or phrases, matches go through the FTS5 index over content,
+    /// source app and window, and tags: best first (bm25, tag matches weigh
+    /// most), then newest. Operator-only queries list newest first. Each hit
+    /// carries an HTML snippet with the matches in `<mark>`.
+    pub async fn search(
+        pool: &SqlitePool,
+        organization_id: &str,
+        query: &SearchQuery,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<SearchHit>, Box<dyn std::error::Error>> {
+        let expression = query.match_expression();
+        let has_text = query
+            .filters
+            .iter()
+            .any(|filter| matches!(filter, Filter::Word(_) | Filter::Phrase(_)));
+        if has_text && expression.is_none() {
+            // Only punctuation was typed; FTS5 has nothing to look for
+            return Ok(Vec::new());
+        }
+
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT e.*, ");
+        match &expression {
+            Some(expression) => {
+                builder
+                    .push("snippet(clipboard_search, -1, ")
+                    .push_bind(fts::MATCH_START)
+                    .push(", ")
+                    .push_bind(fts::MATCH_END)
+                    .push(", '…', 16) AS snippet, bm25(clipboard_search, 1.0, 0.5, 0.5, 2.0) AS rank")
+                    .push(" FROM clipboard_search JOIN clipboard_entries e ON e.id = clipboard_search.rowid")
+                    .push(" WHERE clipboard_search MATCH ")
+                    .push_bind(expression.clone())
+                    .push(" AND ");
+            }
+            None => {
+                builder.push("substr(e.content, 1, 200) AS snippet, 0.0 AS rank FROM clipboard_entries e WHERE ");
+            }
+        }
+        builder.push("e.organization_id = ").push_bind(organization_id);
+
+        for filter in &query.filters {
+            push_filter(&mut builder, filter);
+        }
+
+        builder
+            .push(if expression.is_some() { " ORDER BY rank, e.timestamp DESC" } else { " ORDER BY e.timestamp DESC" })
+            .push(" LIMIT ")
+            .push_bind(limit.unwrap_or(50).clamp(1, 200))
+            .push(" OFFSET ")
+            .push_bind(offset.unwrap_or(0).max(0));
+
+        let mut hits = builder.build_query_as::<SearchHit>().fetch_all(pool).await?;
+        for hit in &mut hits {
+            hit.snippet = fts::highlight_html(&hit.snippet);
+        }
+
+        Ok(hits)
+    }
+
+    /// Entries of a classifier type, whether it is their primary type or
+    /// one of the secondary matches listed in `content_meta`.
+    pub async fn get_by_content_type(
+        pool: &SqlitePool,
+        organization_id: &str,
+        content_type: &str,
+        limit: Option<i64>,
     ) -> Result<Vec<ClipboardEntry>, Box<dyn std::error::Error>> {
-        let search_pattern = format!("%{}%", query);
-        
+        let limit = limit.unwrap_or(100);
+
         let results = sqlx::query_as::<_, ClipboardEntry>(
-            "SELECT * FROM clipboard_entries WHERE content LIKE ?1 ORDER BY created_at DESC"
+            r#"
+            SELECT * FROM clipboard_entries
+            WHERE organization_id = ?1
+              AND (content_type = ?2
+                   OR EXISTS (SELECT 1 FROM json_each(clipboard_entries.content_meta, '$.types')
+                              WHERE json_each.value = ?2))
+            ORDER BY timestamp DESC
+            LIMIT ?3
+            "#
         )
-        .bind(search_pattern)
+        .bind(organization_id)
+        .bind(content_type)
+        .bind(limit)
         .fetch_all(pool)
         .await?;
-        
+
         Ok(results)
     }
-    
+
  pub async fn update_entry(
     pool: &SqlitePool, 
     id: i64, 
+    organization_id: &str,
     update: UpdateClipboardEntry
 ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-    let result = sqlx::query_as::<_, ClipboardEntry>(
+    let mut tx = pool.begin().await?;
+    let mut result = sqlx::query_as::<_, ClipboardEntry>(
         r#"
         UPDATE clipboard_entries 
         SET 
             is_pinned   = COALESCE(?1, is_pinned),
-            tags        = COALESCE(?2, tags),
             sync_status = 'local'
-        WHERE id = ?3
+        WHERE id = ?2 AND organization_id = ?3
         RETURNING *
         "#
     )
     .bind(update.is_pinned)
-    .bind(update.tags)
     .bind(id)
-    .fetch_one(pool)
-    .await?;
+    .bind(organization_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or("Clipboard entry not found")?;
+
+    if let Some(tags) = update.tags {
+        SqliteEntryTagsRepository::replace_names(&mut tx, id, organization_id, &tag_names(Some(&tags))).await?;
+        result = sqlx::query_as::<_, ClipboardEntry>("SELECT * FROM clipboard_entries WHERE id = ?1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
     
     Ok(result)
 }
@@ -449,45 +802,106 @@ impl SqliteClipboardRepository {
     
    pub async fn delete_entry(
     pool: &SqlitePool,
-    id: i64
+    id: i64,
+    organization_id: &str,
     ) -> Result<bool, Box<dyn std::error::Error>> {
     let result = sqlx::query(
         r#"
         DELETE FROM clipboard_entries
-        WHERE id = ?1
+        WHERE id = ?1 AND organization_id = ?2
         "#
     )
     .bind(id)
+    .bind(organization_id)
     .execute(pool)
     .await?;
 
     Ok(result.rows_affected() > 0)
   }
 
+    /// Image files of every entry, in any organization.
+    pub async fn referenced_images(pool: &SqlitePool) -> Result<HashSet<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT DISTINCT image_path FROM clipboard_entries WHERE image_path IS NOT NULL",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(file_name,)| file_name).collect())
+    }
+
+    /// Whether any entry, in any organization, still shows this image file.
+    /// Files are named by content hash, so one PNG can back several rows.
+    pub async fn image_in_use(pool: &SqlitePool, file_name: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM clipboard_entries WHERE image_path = ?1)")
+            .bind(file_name)
+            .fetch_one(pool)
+            .await
+    }
+
 
 
+/// Replaces an entry's text. With `blob_ref` the full text is already in
+/// the blob store and only a preview is kept in the row. Fails with
+/// `DuplicateEntry` when another entry of the organization already has
+/// this text.
 pub async fn update_entry_content(
     pool: &SqlitePool,
     entry_id: i64,
+    organization_id: &str,
     new_content: &str,
+    blob_ref: Option<&str>,
 ) -> Result<ClipboardEntry, Box<dyn std::error::Error>> {
-    
+    let new_hash = content_hash(new_content);
+    let existing: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM clipboard_entries
+        WHERE organization_id = ?3
+          AND content_hash = ?2
+          AND id <> ?1
+        "#,
+    )
+    .bind(entry_id)
+    .bind(&new_hash)
+    .bind(organization_id)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(existing_id) = existing {
+        return Err(Box::new(DuplicateEntry { existing_id }));
+    }
+
+    let classification = classifier::classify(new_content);
+    let stored_content = match blob_ref {
+        Some(_) => blob_store::preview(new_content),
+        None => new_content.to_string(),
+    };
+
     let result = sqlx::query_as::<_, ClipboardEntry>(
         r#"
         UPDATE clipboard_entries 
         SET 
             content   = ?1,
+            content_hash = ?4,
+            content_type = ?5,
+            content_meta = ?6,
+            blob_ref  = ?7,
             timestamp = ?2,
             sync_status = 'local'
-        WHERE id = ?3
+        WHERE id = ?3 AND organization_id = ?8
         RETURNING *
         "#
     )
-    .bind(new_content)
+    .bind(stored_content)
     .bind(to_sqlite_ts(Utc::now()))
     .bind(entry_id)
-    .fetch_one(pool)
-    .await?;
+    .bind(new_hash)
+    .bind(&classification.content_type)
+    .bind(classification.metadata_json())
+    .bind(blob_ref)
+    .bind(organization_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or("Clipboard entry not found")?;
     
     Ok(result)
 }
@@ -496,11 +910,13 @@ pub async fn update_entry_content(
 
  pub async fn exists_by_hash(
         pool: &SqlitePool, 
+        organization_id: &str,
         content_hash: &str
     ) -> Result<bool, Box<dyn std::error::Error>> {
         let result = sqlx::query(
-            "SELECT 1 FROM clipboard_entries WHERE content_hash = ?1 LIMIT 1"
+            "SELECT 1 FROM clipboard_entries WHERE organization_id = ?1 AND content_hash = ?2 LIMIT 1"
         )
+        .bind(organization_id)
         .bind(content_hash)
         .fetch_optional(pool)
         .await?;
@@ -533,7 +949,8 @@ pub async fn update_entry_content(
 
     pub async fn delete_untagged_entries(pool: &SqlitePool, organization_id: &str) -> Result<usize, sqlx::Error> {
         sqlx::query(
-            "DELETE FROM clipboard_entries WHERE organization_id = ?1 AND is_pinned = false AND tags IS NULL"
+            "DELETE FROM clipboard_entries WHERE organization_id = ?1 AND is_pinned = false
+               AND NOT EXISTS (SELECT 1 FROM entry_tags WHERE entry_id = clipboard_entries.id)"
         )
         .bind(organization_id)
         .execute(pool)
@@ -554,115 +971,84 @@ pub async fn update_entry_content(
     pub async fn assign_tag(
         pool: &SqlitePool, 
         clipboard_entry_id: i64, 
+        organization_id: &str,
         tag_name: &str
     ) -> Result<ClipboardEntry, String> {
-        println!("=== ASSIGN TAG DEBUG (SQLite) ===");
         println!("🟢 Assigning tag '{}' to entry {}", tag_name, clipboard_entry_id);
-        
-        // First get the current entry
-        let current_entry = Self::get_by_id(pool, clipboard_entry_id).await
+
+        Self::get_by_id_for_org(pool, clipboard_entry_id, organization_id).await
             .map_err(|e| format!("Database error: {}", e))?
             .ok_or("Clipboard entry not found".to_string())?;
-        
-        println!("📋 Current entry tags: {:?}", current_entry.tags);
-        
-        // Parse current tags
-        let mut current_tags = json_to_tags(&current_entry.tags);
-        println!("📋 Parsed current tags: {:?}", current_tags);
-        
-        // Add the new tag name if not already present
-        if !current_tags.contains(&tag_name.to_string()) {
-            current_tags.push(tag_name.to_string());
-            println!("✅ Added tag '{}'", tag_name);
-        } else {
-            println!("ℹ️ Tag '{}' already exists", tag_name);
-        }
-        
-        println!("📋 New tags: {:?}", current_tags);
-        
-        // Convert back to JSON
-        let new_tags_json = tags_to_json(&current_tags);
-        println!("📋 New tags JSON: {:?}", new_tags_json);
-        
-        // Update the entry
-        let update = UpdateClipboardEntry {
-            tags: new_tags_json,
-            is_pinned: None,
-        };
-        
-        let result = Self::update_entry(pool, clipboard_entry_id, update).await
+
+        SqliteEntryTagsRepository::add_names(pool, clipboard_entry_id, organization_id, &[tag_name.to_string()])
+            .await
             .map_err(|e| format!("Update failed: {}", e))?;
-        
-        println!("✅ Success! Updated entry tags: {:?}", json_to_tags(&result.tags));
-        println!("=== ASSIGN TAG DEBUG END ===");
-        
+
+        let result = Self::mark_tags_changed(pool, clipboard_entry_id).await?;
+        println!("✅ Entry {} tags: {:?}", clipboard_entry_id, result.tag_names());
         Ok(result)
     }
 
     pub async fn remove_tag(
         pool: &SqlitePool, 
         clipboard_entry_id: i64, 
+        organization_id: &str,
         tag_name: &str
     ) -> Result<ClipboardEntry, String> {
-        println!("=== REMOVE TAG DEBUG (SQLite) ===");
         println!("🔴 Removing tag '{}' from entry {}", tag_name, clipboard_entry_id);
-        
-        // For offline mode, we don't need organization check
-        let current_entry = Self::get_by_id(pool, clipboard_entry_id).await
+
+        Self::get_by_id_for_org(pool, clipboard_entry_id, organization_id).await
             .map_err(|e| format!("Database error: {}", e))?
             .ok_or("Clipboard entry not found".to_string())?;
-        
-        println!("📋 Current entry tags: {:?}", current_entry.tags);
-        
-        // Parse current tags and remove the specified tag name
-        let mut current_tags = json_to_tags(&current_entry.tags);
-        println!("📋 Parsed current tags: {:?}", current_tags);
-        
-        let before_count = current_tags.len();
-        current_tags.retain(|name| name != tag_name);
-        let after_count = current_tags.len();
-        
-        println!("📋 Tags before: {}, after: {}", before_count, after_count);
-        println!("📋 New tags: {:?}", current_tags);
-        
-        // Convert back to JSON - FIXED: use tags_to_json instead of tags_to_tags
-        let new_tags_json = tags_to_json(&current_tags);
-        println!("📋 New tags JSON: {:?}", new_tags_json);
-        
-        // Update the entry
-       let result = sqlx::query_as::<_, ClipboardEntry>(
-    r#"
-    UPDATE clipboard_entries 
-    SET 
-        tags        = ?1,
-        sync_status = 'local'
-    WHERE id = ?2
-    RETURNING *
-    "#
-)
-.bind(&new_tags_json)
-.bind(clipboard_entry_id)
-.fetch_one(pool)
-.await
-
-        .map_err(|e| format!("Update failed: {}", e))?;
-        
-        println!("✅ Database update successful!");
-        println!("✅ Updated entry tags from query: {:?}", result.tags);
-        
-        // Verify the update
-        let verified_entry = Self::get_by_id(pool, clipboard_entry_id).await
-            .map_err(|e| format!("Verification failed: {}", e))?
-            .ok_or("Could not verify update - entry not found".to_string())?;
-        
-        let verified_tags = json_to_tags(&verified_entry.tags);
-        println!("✅ Verified tags after update: {:?}", verified_tags);
-        println!("✅ Raw verified tags: {:?}", verified_entry.tags);
-        
-        println!("=== REMOVE TAG DEBUG END ===");
+
+        let removed = SqliteEntryTagsRepository::remove_name(pool, clipboard_entry_id, organization_id, tag_name)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))?;
+        if !removed {
+            println!("ℹ️ Entry {} has no tag '{}'", clipboard_entry_id, tag_name);
+        }
+
+        let result = Self::mark_tags_changed(pool, clipboard_entry_id).await?;
+        println!("✅ Entry {} tags: {:?}", clipboard_entry_id, result.tag_names());
         Ok(result)
     }
 
+    /// Flags an entry whose tags changed locally so the next sync pushes it.
+    async fn mark_tags_changed(pool: &SqlitePool, clipboard_entry_id: i64) -> Result<ClipboardEntry, String> {
+        sqlx::query_as::<_, ClipboardEntry>(
+            "UPDATE clipboard_entries SET sync_status = 'local' WHERE id = ?1 RETURNING *",
+        )
+        .bind(clipboard_entry_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Update failed: {}", e))
+    }
+
+    /// Entries linked to a tag, newest first.
+    pub async fn get_by_tag(
+        pool: &SqlitePool,
+        organization_id: &str,
+        tag_id: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<ClipboardEntry>, Box<dyn std::error::Error>> {
+        let results = sqlx::query_as::<_, ClipboardEntry>(
+            r#"
+            SELECT e.* FROM clipboard_entries e
+            JOIN entry_tags et ON et.entry_id = e.id
+            WHERE et.tag_id = ?1 AND e.organization_id = ?2
+            ORDER BY e.timestamp DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(tag_id)
+        .bind(organization_id)
+        .bind(limit.unwrap_or(100))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(results)
+    }
+
     // Additional offline-specific methods
     pub async fn get_pending_sync_entries(pool: &SqlitePool) -> Result<Vec<ClipboardEntry>, Box<dyn std::error::Error>> {
         let results = sqlx::query_as::<_, ClipboardEntry>(
@@ -687,6 +1073,7 @@ pub async fn update_entry_content(
             FROM clipboard_entries
             WHERE organization_id = ?1
               AND sync_status = 'local'
+              AND is_sensitive = FALSE
             ORDER BY created_at ASC
             LIMIT ?2
             "#
@@ -714,4 +1101,188 @@ pub async fn update_entry_content(
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn save(pool: &SqlitePool, organization_id: &str, content: &str) -> ClipboardEntry {
+        let mut entry = NewClipboardEntry::from_monitoring_data(
+            content.to_string(),
+            "editor".to_string(),
+            "notes.txt".to_string(),
+        );
+        entry.organization_id = Some(organization_id.to_string());
+        SqliteClipboardRepository::save_entry(pool, entry).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn editing_content_rehashes_and_reclassifies() {
+        let pool = memory_pool().await;
+        let entry = save(&pool, "org-a", "draft").await;
+
+        let updated = SqliteClipboardRepository::update_entry_content(&pool, entry.id, "org-a", "https://example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(updated.content, "https://example.com");
+        assert_eq!(updated.content_hash, content_hash("https://example.com"));
+        assert_eq!(updated.content_type, "url");
+
+        // Saving the entry's own text again is not a duplicate
+        SqliteClipboardRepository::update_entry_content(&pool, entry.id, "org-a", "https://example.com", None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn editing_into_another_entrys_content_is_a_duplicate() {
+        let pool = memory_pool().await;
+        let first = save(&pool, "org-a", "first").await;
+        let second = save(&pool, "org-a", "second").await;
+
+        let error = SqliteClipboardRepository::update_entry_content(&pool, second.id, "org-a", "first", None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DuplicateEntry>(),
+            Some(&DuplicateEntry { existing_id: first.id })
+        );
+
+        let unchanged = SqliteClipboardRepository::get_by_id_for_org(&pool, second.id, "org-a").await.unwrap().unwrap();
+        assert_eq!(unchanged.content, "second");
+    }
+
+    #[tokio::test]
+    async fn the_same_content_in_another_organization_is_not_a_duplicate() {
+        let pool = memory_pool().await;
+        save(&pool, "org-a", "shared").await;
+        let other = save(&pool, "org-b", "draft").await;
+
+        let updated = SqliteClipboardRepository::update_entry_content(&pool, other.id, "org-b", "shared", None)
+            .await
+            .unwrap();
+        assert_eq!(updated.content_hash, content_hash("shared"));
+    }
+
+    async fn search_contents(pool: &SqlitePool, input: &str) -> Vec<String> {
+        let query = query::parse(input).unwrap();
+        let mut contents: Vec<String> = SqliteClipboardRepository::search(pool, "org-a", &query, None, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.entry.content)
+            .collect();
+        contents.sort();
+        contents
+    }
+
+    #[tokio::test]
+    async fn negated_terms_exclude_entries() {
+        let pool = memory_pool().await;
+        save(&pool, "org-a", "draft report").await;
+        save(&pool, "org-a", "final report").await;
+        save(&pool, "org-a", "Error: timeout").await;
+        save(&pool, "org-b", "other report").await;
+
+        assert_eq!(search_contents(&pool, "report -draft").await, vec!["final report"]);
+        assert_eq!(search_contents(&pool, "-report").await, vec!["Error: timeout"]);
+        assert_eq!(search_contents(&pool, r#"-"final report" -error"#).await, vec!["draft report"]);
+        assert_eq!(search_contents(&pool, r#""Error: timeout""#).await, vec!["Error: timeout"]);
+        assert_eq!(search_contents(&pool, "report -pinned:true").await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_by_ids_is_scoped_to_the_organization() {
+        let pool = memory_pool().await;
+        let first = save(&pool, "org-a", "first").await;
+        let second = save(&pool, "org-a", "second").await;
+        let foreign = save(&pool, "org-b", "foreign").await;
+
+        let mut ids: Vec<i64> = SqliteClipboardRepository::get_by_ids(&pool, "org-a", &[second.id, foreign.id, first.id, 9999])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![first.id, second.id]);
+
+        assert!(SqliteClipboardRepository::get_by_ids(&pool, "org-a", &[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_by_id_for_org_hides_other_organizations() {
+        let pool = memory_pool().await;
+        let entry = save(&pool, "org-a", "mine").await;
+
+        let found = SqliteClipboardRepository::get_by_id_for_org(&pool, entry.id, "org-a").await.unwrap();
+        assert_eq!(found.map(|e| e.content).as_deref(), Some("mine"));
+        assert!(SqliteClipboardRepository::get_by_id_for_org(&pool, entry.id, "org-b").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn sensitive_recopy_does_not_expire_a_pinned_entry() {
+        let pool = memory_pool().await;
+        let entry = save(&pool, "org-a", "hunter2").await;
+        sqlx::query("UPDATE clipboard_entries SET is_pinned = 1 WHERE id = ?1")
+            .bind(entry.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut recopy = NewClipboardEntry::from_monitoring_data(
+            "hunter2".to_string(),
+            "editor".to_string(),
+            "notes.txt".to_string(),
+        );
+        recopy.organization_id = Some("org-a".to_string());
+        recopy.is_sensitive = true;
+        recopy.expires_at = Some(Utc::now() - chrono::Duration::minutes(1));
+        let saved = SqliteClipboardRepository::save_entry(&pool, recopy).await.unwrap();
+        assert!(saved.is_pinned);
+        assert!(saved.expires_at.is_none());
+
+        // Pinned before the policy change: an expiry is already set
+        sqlx::query("UPDATE clipboard_entries SET expires_at = ?1 WHERE id = ?2")
+            .bind(to_sqlite_ts(Utc::now() - chrono::Duration::minutes(1)))
+            .bind(entry.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(SqliteClipboardRepository::delete_expired_entries(&pool).await.unwrap(), 0);
+        assert!(SqliteClipboardRepository::get_by_id_for_org(&pool, entry.id, "org-a").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn editing_another_organizations_entry_is_not_found() {
+        let pool = memory_pool().await;
+        let entry = save(&pool, "org-a", "mine").await;
+
+        let error = SqliteClipboardRepository::update_entry_content(&pool, entry.id, "org-b", "taken", None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Clipboard entry not found");
+
+        let unchanged = SqliteClipboardRepository::get_by_id_for_org(&pool, entry.id, "org-a").await.unwrap().unwrap();
+        assert_eq!(unchanged.content, "mine");
+    }
+
+    #[tokio::test]
+    async fn entries_of_other_organizations_cannot_be_changed() {
+        let pool = memory_pool().await;
+        let entry = save(&pool, "org-a", "mine").await;
+
+        let pin = UpdateClipboardEntry { is_pinned: Some(true), ..Default::default() };
+        assert!(SqliteClipboardRepository::update_entry(&pool, entry.id, "org-b", pin).await.is_err());
+        assert!(SqliteClipboardRepository::assign_tag(&pool, entry.id, "org-b", "work").await.is_err());
+        assert!(SqliteClipboardRepository::remove_tag(&pool, entry.id, "org-b", "work").await.is_err());
+        assert!(!SqliteClipboardRepository::delete_entry(&pool, entry.id, "org-b").await.unwrap());
+
+        let unchanged = SqliteClipboardRepository::get_by_id_for_org(&pool, entry.id, "org-a").await.unwrap().unwrap();
+        assert!(!unchanged.is_pinned);
+        assert!(unchanged.tag_names().is_empty());
+
+        let tagged = SqliteClipboardRepository::assign_tag(&pool, entry.id, "org-a", "work").await.unwrap();
+        assert_eq!(tagged.tag_names(), vec!["work"]);
+    }
+}
@@ -0,0 +1,151 @@
+// src/db/sqlite_entry_tags_repository.rs
+//
+// Links between clipboard entries and tags. `entry_tags` is the source of
+// truth; `clipboard_entries.tags` is a JSON array of the linked names that
+// triggers keep current (see the `entry_tags` SQLite migration), so renaming
+// or deleting a tag updates every entry that carries it.
+//
+// Entries and tags are matched across devices by name, so callers pass tag
+// names; tags that don't exist yet are created and uploaded on the next sync.
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqlitePool};
+
+pub struct SqliteEntryTagsRepository;
+
+impl SqliteEntryTagsRepository {
+    /// Adds the named tags to the entry, keeping the ones it already has.
+    pub async fn add_names(
+        pool: &SqlitePool,
+        entry_id: i64,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        Self::link_names(&mut tx, entry_id, organization_id, names).await?;
+        tx.commit().await
+    }
+
+    /// Removes the tag from the entry. Returns whether it was there.
+    pub async fn remove_name(
+        pool: &SqlitePool,
+        entry_id: i64,
+        organization_id: &str,
+        name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM entry_tags
+            WHERE entry_id = ?1
+              AND tag_id IN (SELECT id FROM tags WHERE organization_id = ?2 AND LOWER(name) = LOWER(?3))
+            "#,
+        )
+        .bind(entry_id)
+        .bind(organization_id)
+        .bind(name.trim())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn link_names(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        for tag_id in Self::tag_ids_for_names(conn, organization_id, names).await? {
+            sqlx::query("INSERT OR IGNORE INTO entry_tags (entry_id, tag_id) VALUES (?1, ?2)")
+                .bind(entry_id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn replace_names(
+        conn: &mut SqliteConnection,
+        entry_id: i64,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let keep = Self::tag_ids_for_names(conn, organization_id, names).await?;
+
+        let current: Vec<i64> = sqlx::query_scalar("SELECT tag_id FROM entry_tags WHERE entry_id = ?1")
+            .bind(entry_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        for tag_id in current.into_iter().filter(|id| !keep.contains(id)) {
+            sqlx::query("DELETE FROM entry_tags WHERE entry_id = ?1 AND tag_id = ?2")
+                .bind(entry_id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        for tag_id in keep {
+            sqlx::query("INSERT OR IGNORE INTO entry_tags (entry_id, tag_id) VALUES (?1, ?2)")
+                .bind(entry_id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Ids of the organization's tags with these names (case-insensitive),
+    /// creating the missing ones.
+    pub async fn tag_ids_for_names(
+        conn: &mut SqliteConnection,
+        organization_id: &str,
+        names: &[String],
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+
+        for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            if !seen.insert(name.to_lowercase()) {
+                continue;
+            }
+
+            let existing: Option<i64> = sqlx::query_scalar(
+                "SELECT id FROM tags WHERE organization_id = ?1 AND LOWER(name) = LOWER(?2) ORDER BY id LIMIT 1",
+            )
+            .bind(organization_id)
+            .bind(name)
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    let now = Utc::now();
+                    println!("🏷️ Creating tag '{}' for a tagged entry", name);
+                    sqlx::query_scalar(
+                        r#"
+                        INSERT INTO tags (organization_id, name, created_at, updated_at, sync_status)
+                        VALUES (?1, ?2, ?3, ?4, 'local')
+                        RETURNING id
+                        "#,
+                    )
+                    .bind(organization_id)
+                    .bind(name)
+                    .bind(now)
+                    .bind(now)
+                    .fetch_one(&mut *conn)
+                    .await?
+                }
+            };
+
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+}
@@ -0,0 +1,844 @@
+// src/db/sqlite_migrations.rs
+//
+// Versioned schema for the local SQLite database. `schema_version` records
+// every migration that has run; on start-up the ones with a higher version
+// are applied in order. Migrations are append-only: never edit or renumber
+// one that has shipped, add a new one instead.
+//
+// Each migration records its version only after it succeeds, and is written
+// so that running it again after a crash halfway is harmless.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+
+use crate::capture::classifier;
+use crate::db::blob_store;
+use crate::db::database::{json_to_tags, tags_to_json};
+use crate::db::schemas::capture_settings::DEFAULT_MAX_INLINE_BYTES;
+use crate::db::schemas::clipboard::content_hash;
+use crate::db::sqlite_database::{get_data_dir, DATABASE_FILE_NAME};
+use crate::db::sqlite_entry_tags_repository::SqliteEntryTagsRepository;
+
+struct Migration {
+    version: i64,
+    name: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "initial_schema" },
+    Migration { version: 2, name: "dedupe_per_organization" },
+    Migration { version: 3, name: "import_legacy_databases" },
+    Migration { version: 4, name: "sha256_content_hashes" },
+    Migration { version: 5, name: "classify_legacy_entries" },
+    Migration { version: 6, name: "large_content_blobs" },
+    Migration { version: 7, name: "entry_tags" },
+    Migration { version: 8, name: "content_search_index" },
+    Migration { version: 9, name: "history_keyset_indexes" },
+];
+
+async fn apply(pool: &SqlitePool, migration: &Migration) -> Result<(), Box<dyn std::error::Error>> {
+    match migration.version {
+        1 => create_initial_schema(pool).await,
+        2 => scope_content_hash_to_organization(pool).await,
+        3 => import_legacy_databases(pool).await,
+        4 => rehash_md5_entries(pool).await,
+        5 => classify_legacy_entries(pool).await,
+        6 => move_large_entries_to_blobs(pool).await,
+        7 => create_entry_tags(pool).await,
+        8 => create_content_search_index(pool).await,
+        9 => create_history_keyset_indexes(pool).await,
+        version => Err(format!("No SQLite migration with version {}", version).into()),
+    }
+}
+
+/// Brings the database up to the latest schema version.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let current = schema_version(pool).await?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if current > latest {
+        // Written by a newer ClipTray; its extra columns have defaults, so carry on
+        eprintln!("⚠️ SQLite schema version {} is newer than this build ({})", current, latest);
+        return Ok(());
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!("📝 Applying SQLite migration {} ({})...", migration.version, migration.name);
+        apply(pool, migration).await.map_err(|e| {
+            format!("SQLite migration {} ({}) failed: {}", migration.version, migration.name, e)
+        })?;
+
+        sqlx::query("INSERT INTO schema_version (version, name) VALUES (?1, ?2)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(pool)
+            .await?;
+    }
+
+    println!("✅ SQLite schema at version {}", latest);
+    Ok(())
+}
+
+pub async fn schema_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await
+}
+
+/// Tables brought over from older database files, with an optional filter
+/// on the legacy rows (`l`). Conflicts on unique keys keep the current row.
+const LEGACY_TABLES: &[(&str, &str)] = &[
+    ("users", ""),
+    ("tags", ""),
+    (
+        "payments",
+        "WHERE NOT EXISTS (SELECT 1 FROM main.payments p WHERE p.stripe_session_id = l.stripe_session_id)",
+    ),
+    (
+        // Text hashes may still be md5 there, so dedupe on the content itself
+        "clipboard_entries",
+        "WHERE l.content_type = 'image' OR NOT EXISTS (
+            SELECT 1 FROM main.clipboard_entries m
+            WHERE m.organization_id IS l.organization_id AND m.content = l.content
+        )",
+    ),
+];
+
+/// Earlier releases moved to a new database file (`cliptray_offline.db`,
+/// `cliptray_offline2.db`) instead of migrating, leaving history behind.
+/// Copy it into the current database, newest file first, and rename each
+/// file to `*.imported` once done so it is never read twice.
+async fn import_legacy_databases(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    for path in legacy_database_paths() {
+        match import_legacy_database(pool, &path).await {
+            Ok(()) => {
+                let imported = path.with_extension("db.imported");
+                if let Err(e) = fs::rename(&path, &imported) {
+                    eprintln!("⚠️ Imported {} but could not rename it: {}", path.display(), e);
+                }
+            }
+            // A damaged old file shouldn't keep the app from starting
+            Err(e) => eprintln!("⚠️ Could not import {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(())
+}
+
+fn legacy_database_paths() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(get_data_dir()) else {
+        return Vec::new();
+    };
+
+    let mut legacy: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name == DATABASE_FILE_NAME {
+                return None;
+            }
+            let suffix = name.strip_prefix("cliptray_offline")?.strip_suffix(".db")?;
+            let generation = match suffix {
+                "" => 1,
+                digits => digits.parse().ok()?,
+            };
+            Some((generation, entry.path()))
+        })
+        .collect();
+
+    legacy.sort_by_key(|&(generation, _)| std::cmp::Reverse(generation));
+    legacy.into_iter().map(|(_, path)| path).collect()
+}
+
+async fn import_legacy_database(pool: &SqlitePool, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("📥 Importing data from {}...", path.display());
+
+    // ATTACH is per connection and can't run inside a transaction
+    let mut conn = pool.acquire().await?;
+    sqlx::query("ATTACH DATABASE ?1 AS legacy")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(&mut *conn)
+        .await?;
+
+    let result = copy_legacy_tables(&mut conn).await.map_err(|e| e.to_string());
+
+    sqlx::query("DETACH DATABASE legacy").execute(&mut *conn).await?;
+    Ok(result?)
+}
+
+async fn copy_legacy_tables(conn: &mut SqliteConnection) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = conn.begin().await?;
+
+    for (table, filter) in LEGACY_TABLES {
+        let legacy_columns: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?1, 'legacy')")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+        if legacy_columns.is_empty() {
+            // Table didn't exist in that release
+            continue;
+        }
+
+        let current_columns: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?1, 'main')")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        // Ids are reassigned; columns added since keep their defaults
+        let columns: Vec<&String> = legacy_columns
+            .iter()
+            .filter(|c| *c != "id" && current_columns.contains(c))
+            .collect();
+        let insert_list = columns.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", ");
+        let select_list = columns.iter().map(|c| format!("l.\"{}\"", c)).collect::<Vec<_>>().join(", ");
+
+        let result = sqlx::query(&format!(
+            "INSERT OR IGNORE INTO main.{0} ({1}) SELECT {2} FROM legacy.{0} AS l {3}",
+            table, insert_list, select_list, filter
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        println!("📥 Imported {} rows into {}", result.rows_affected(), table);
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Schema as it stood when versioning was introduced. Installs from before
+/// that already have some of it, so every statement is idempotent.
+async fn create_initial_schema(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    println!("📝 Creating Users table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS users (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            organization_id TEXT NOT NULL,
+            firebase_uid TEXT UNIQUE NOT NULL,
+            email TEXT NOT NULL,
+            display_name TEXT,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            purge_cadence TEXT NOT NULL DEFAULT 'never',
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            last_login_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            retain_tags BOOLEAN NOT NULL DEFAULT FALSE,
+            plan TEXT NOT NULL DEFAULT 'free'
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    
+
+    println!("📝 Creating clipboard table if not exists...");
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS clipboard_entries ({})",
+        CLIPBOARD_ENTRIES_COLUMNS
+    ))
+    .execute(pool)
+    .await?;
+
+    // Columns added after the first release; CREATE TABLE IF NOT EXISTS
+    // does not touch existing installs, so add them explicitly.
+    add_column_if_missing(pool, "clipboard_entries", "image_width", "INTEGER").await?;
+    add_column_if_missing(pool, "clipboard_entries", "image_height", "INTEGER").await?;
+    add_column_if_missing(pool, "clipboard_entries", "byte_size", "INTEGER").await?;
+    add_column_if_missing(pool, "clipboard_entries", "image_path", "TEXT").await?;
+    add_column_if_missing(pool, "clipboard_entries", "is_sensitive", "BOOLEAN NOT NULL DEFAULT FALSE").await?;
+    add_column_if_missing(pool, "clipboard_entries", "expires_at", "DATETIME").await?;
+    add_column_if_missing(pool, "clipboard_entries", "copy_count", "INTEGER NOT NULL DEFAULT 1").await?;
+    add_column_if_missing(pool, "clipboard_entries", "last_source_app", "TEXT").await?;
+    add_column_if_missing(pool, "clipboard_entries", "content_html", "TEXT").await?;
+    add_column_if_missing(pool, "clipboard_entries", "file_list", "TEXT").await?;
+    add_column_if_missing(pool, "clipboard_entries", "content_meta", "TEXT").await?;
+    add_column_if_missing(pool, "clipboard_entries", "blob_ref", "TEXT").await?;
+
+    println!("📝 Creating Tags table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            organization_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            color TEXT NOT NULL DEFAULT '#6B7280',
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            sync_status TEXT NOT NULL DEFAULT 'local',
+        server_id INTEGER
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    println!("📝 Creating Payments table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            stripe_session_id TEXT NOT NULL,
+            stripe_payment_intent_id TEXT,
+            organization_id TEXT NOT NULL,
+            firebase_uid TEXT NOT NULL,
+            email TEXT NOT NULL,
+            amount_paid INTEGER NOT NULL DEFAULT 0,
+            currency TEXT NOT NULL DEFAULT 'usd',
+            payment_status TEXT NOT NULL DEFAULT 'unpaid' CHECK(payment_status IN ('paid', 'unpaid', 'failed')),
+            plan_type TEXT NOT NULL DEFAULT 'lifetime',
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            paid_at DATETIME,
+            metadata TEXT
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    println!("📝 Creating Capture settings table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS capture_settings (
+            organization_id TEXT PRIMARY KEY,
+            settings TEXT NOT NULL DEFAULT '{}',
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            sync_status TEXT NOT NULL DEFAULT 'local'
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    println!("📝 Creating Content blobs table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS content_blobs (
+            organization_id TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            byte_size INTEGER NOT NULL,
+            stored_size INTEGER NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            sync_status TEXT NOT NULL DEFAULT 'local',
+            PRIMARY KEY (organization_id, hash)
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    println!("📝 Creating Capture rules table if not exists...");
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS capture_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            organization_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
+            position INTEGER NOT NULL DEFAULT 0,
+            conditions TEXT NOT NULL DEFAULT '{}',
+            actions TEXT NOT NULL DEFAULT '{}',
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            sync_status TEXT NOT NULL DEFAULT 'local',
+            server_id INTEGER
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    // === Indexes ===
+    println!("📝 Creating indexes if not exist...");
+    
+    // Users indexes
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_firebase_uid ON users(firebase_uid)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_organization_id ON users(organization_id)")
+        .execute(pool).await?;
+
+    // Clipboard indexes
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_content_hash ON clipboard_entries(content_hash)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_created_at ON clipboard_entries(created_at DESC)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_source_app ON clipboard_entries(source_app)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_organization_id ON clipboard_entries(organization_id)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_clipboard_sync_status ON clipboard_entries(sync_status)")
+        .execute(pool).await?;
+
+    // Tags indexes
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_organization_id ON tags(organization_id)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name)")
+        .execute(pool).await?;
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_organization_name_unique ON tags(organization_id, name)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tags_sync_status ON tags(sync_status)")
+    .execute(pool).await?;
+
+    // Capture rules indexes
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_capture_rules_organization_id ON capture_rules(organization_id)")
+        .execute(pool).await?;
+
+    // Payments indexes
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_firebase_uid ON payments(firebase_uid)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_organization_id ON payments(organization_id)")
+        .execute(pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_payments_stripe_session_id ON payments(stripe_session_id)")
+        .execute(pool).await?;
+
+    Ok(())
+}
+
+/// Column definitions for `clipboard_entries`, shared by the initial CREATE
+/// and the rebuild in `scope_content_hash_to_organization`. Uniqueness of
+/// `content_hash` is per organization, enforced by
+/// `idx_clipboard_org_content_hash`.
+const CLIPBOARD_ENTRIES_COLUMNS: &str = r#"
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    organization_id TEXT,
+    content TEXT NOT NULL,
+    content_type TEXT NOT NULL DEFAULT 'text',
+    content_hash TEXT NOT NULL,
+    source_app TEXT NOT NULL,
+    source_window TEXT NOT NULL,
+    timestamp DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    tags TEXT,
+    is_pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    sync_status TEXT NOT NULL DEFAULT 'local',
+    server_id TEXT,
+    image_width INTEGER,
+    image_height INTEGER,
+    byte_size INTEGER,
+    image_path TEXT,
+    is_sensitive BOOLEAN NOT NULL DEFAULT FALSE,
+    expires_at DATETIME,
+    copy_count INTEGER NOT NULL DEFAULT 1,
+    last_source_app TEXT,
+    content_html TEXT,
+    file_list TEXT,
+    content_meta TEXT,
+    blob_ref TEXT
+"#;
+
+/// Older installs declared `content_hash TEXT UNIQUE`, so the same text copied
+/// under two accounts on one machine collided. SQLite can't drop a column
+/// constraint, so the table is rebuilt without it.
+async fn scope_content_hash_to_organization(
+    pool: &SqlitePool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let has_global_unique = sqlx::query(
+        r#"
+        SELECT 1
+        FROM pragma_index_list('clipboard_entries') AS l
+        JOIN pragma_index_info(l.name) AS i
+        WHERE l.origin = 'u' AND i.name = 'content_hash'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?
+    .is_some();
+
+    if has_global_unique {
+        rebuild_clipboard_entries(pool).await?;
+    }
+
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_clipboard_org_content_hash ON clipboard_entries(organization_id, content_hash)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn rebuild_clipboard_entries(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    println!("📝 Rebuilding clipboard_entries to dedupe per organization...");
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(&format!(
+        "CREATE TABLE clipboard_entries_new ({})",
+        CLIPBOARD_ENTRIES_COLUMNS
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    let columns: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM pragma_table_info('clipboard_entries_new')")
+            .fetch_all(&mut *tx)
+            .await?;
+    let columns = columns.join(", ");
+
+    sqlx::query(&format!(
+        "INSERT INTO clipboard_entries_new ({0}) SELECT {0} FROM clipboard_entries",
+        columns
+    ))
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DROP TABLE clipboard_entries").execute(&mut *tx).await?;
+    sqlx::query("ALTER TABLE clipboard_entries_new RENAME TO clipboard_entries")
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Text entries saved before the switch to SHA-256 still carry 32-char md5
+/// hashes and would never dedupe against new copies. Image hashes cover the
+/// pixels, which aren't kept in the row, so those are left as they are.
+///
+/// An entry pulled from the cloud may already hold the new hash of the same
+/// text. The md5 row is then folded into it: the pin is kept, copy counts
+/// add up and tags are merged (they still live in the JSON `tags` column at
+/// this version).
+async fn rehash_md5_entries(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let legacy: Vec<(i64, Option<String>, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, organization_id, content, tags FROM clipboard_entries WHERE length(content_hash) = 32 AND content_type != 'image'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if legacy.is_empty() {
+        return Ok(());
+    }
+
+    println!("📝 Rehashing {} clipboard entries with SHA-256...", legacy.len());
+    let mut tx = pool.begin().await?;
+    for (id, organization_id, content, legacy_tags) in legacy {
+        let hash = content_hash(&content);
+
+        let existing: Option<(i64, Option<String>)> = sqlx::query_as(
+            "SELECT id, tags FROM clipboard_entries WHERE organization_id IS ?1 AND content_hash = ?2 AND id != ?3",
+        )
+        .bind(&organization_id)
+        .bind(&hash)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((existing_id, existing_tags)) = existing else {
+            sqlx::query("UPDATE clipboard_entries SET content_hash = ?1 WHERE id = ?2")
+                .bind(&hash)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            continue;
+        };
+
+        let mut tags = json_to_tags(&existing_tags);
+        for tag in json_to_tags(&legacy_tags) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE clipboard_entries SET
+                is_pinned   = clipboard_entries.is_pinned OR legacy.is_pinned,
+                copy_count  = clipboard_entries.copy_count + legacy.copy_count,
+                timestamp   = max(clipboard_entries.timestamp, legacy.timestamp),
+                tags        = ?1,
+                sync_status = 'local'
+            FROM (SELECT is_pinned, copy_count, timestamp FROM clipboard_entries WHERE id = ?2) AS legacy
+            WHERE clipboard_entries.id = ?3
+            "#,
+        )
+        .bind(tags_to_json(&tags))
+        .bind(id)
+        .bind(existing_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM clipboard_entries WHERE id = ?1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Entries saved before the blob store hold huge copies inline. Move them
+/// out with the default limit and let the next sync push the preview.
+async fn move_large_entries_to_blobs(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let large: Vec<(i64, Option<String>, String)> = sqlx::query_as(
+        "SELECT id, organization_id, content FROM clipboard_entries
+         WHERE blob_ref IS NULL AND length(CAST(content AS BLOB)) > ?1 AND content_type NOT IN ('image', 'files')",
+    )
+    .bind(DEFAULT_MAX_INLINE_BYTES as i64)
+    .fetch_all(pool)
+    .await?;
+
+    if large.is_empty() {
+        return Ok(());
+    }
+
+    println!("🗜️ Moving {} large clipboard entries to the blob store...", large.len());
+    for (id, organization_id, content) in large {
+        let blob = blob_store::save_blob(&content)?;
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO content_blobs (organization_id, hash, byte_size, stored_size) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(organization_id, hash) DO NOTHING",
+        )
+        .bind(organization_id.unwrap_or_default())
+        .bind(&blob.hash)
+        .bind(blob.byte_size as i64)
+        .bind(blob.stored_size as i64)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE clipboard_entries SET content = ?1, blob_ref = ?2, sync_status = 'local' WHERE id = ?3")
+            .bind(blob_store::preview(&content))
+            .bind(&blob.hash)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Entries captured before the classifier only know the old coarse type.
+/// Run them through it once so type filters cover the whole history.
+async fn classify_legacy_entries(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let legacy: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, content FROM clipboard_entries WHERE content_meta IS NULL AND content_type NOT IN ('image', 'files')",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if legacy.is_empty() {
+        return Ok(());
+    }
+
+    println!("🏷️ Classifying {} clipboard entries...", legacy.len());
+    let mut tx = pool.begin().await?;
+    for (id, content) in legacy {
+        let classification = classifier::classify(&content);
+        sqlx::query("UPDATE clipboard_entries SET content_type = ?1, content_meta = ?2 WHERE id = ?3")
+            .bind(&classification.content_type)
+            .bind(classification.metadata_json())
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// JSON array of an entry's tag names in the order they were added, NULL
+/// when it has none. `{entry}` is the expression giving the entry id.
+const ENTRY_TAG_NAMES: &str = r#"
+    (SELECT json_group_array(name) FROM (
+        SELECT t.name FROM entry_tags et JOIN tags t ON t.id = et.tag_id
+        WHERE et.entry_id = {entry}
+        ORDER BY et.created_at, et.rowid
+    ) HAVING COUNT(*) > 0)
+"#;
+
+/// Moves entry tags from the JSON `clipboard_entries.tags` column into
+/// `entry_tags`. The column stays as a cache of the linked names that
+/// triggers refresh whenever a link changes or a tag is renamed; deleting a
+/// tag cascades to its links and so to the cache as well.
+async fn create_entry_tags(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS entry_tags (
+            entry_id INTEGER NOT NULL REFERENCES clipboard_entries(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (entry_id, tag_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_entry_tags_tag_id ON entry_tags(tag_id)")
+        .execute(pool)
+        .await?;
+
+    let triggers = [
+        ("entry_tags_after_insert", "AFTER INSERT ON entry_tags", "WHERE id = NEW.entry_id", "NEW.entry_id"),
+        ("entry_tags_after_delete", "AFTER DELETE ON entry_tags", "WHERE id = OLD.entry_id", "OLD.entry_id"),
+        (
+            "tags_after_rename",
+            "AFTER UPDATE OF name ON tags WHEN OLD.name IS NOT NEW.name",
+            "WHERE id IN (SELECT entry_id FROM entry_tags WHERE tag_id = NEW.id)",
+            "clipboard_entries.id",
+        ),
+    ];
+    for (name, event, filter, entry) in triggers {
+        sqlx::query(&format!(
+            "CREATE TRIGGER IF NOT EXISTS {} {} BEGIN UPDATE clipboard_entries SET tags = {} {}; END",
+            name,
+            event,
+            ENTRY_TAG_NAMES.replace("{entry}", entry),
+            filter
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    let tagged: Vec<(i64, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT id, organization_id, tags FROM clipboard_entries
+        WHERE tags IS NOT NULL AND tags NOT IN ('', '[]', 'null')
+          AND NOT EXISTS (SELECT 1 FROM entry_tags WHERE entry_id = clipboard_entries.id)
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if !tagged.is_empty() {
+        println!("🏷️ Linking tags of {} clipboard entries...", tagged.len());
+    }
+
+    let mut tx = pool.begin().await?;
+    for (id, organization_id, tags) in tagged {
+        SqliteEntryTagsRepository::link_names(&mut tx, id, &organization_id.unwrap_or_default(), &json_to_tags(&tags))
+            .await?;
+    }
+
+    // Rewrite every cache in the canonical form, including unparseable ones
+    sqlx::query(&format!(
+        "UPDATE clipboard_entries SET tags = {}",
+        ENTRY_TAG_NAMES.replace("{entry}", "clipboard_entries.id")
+    ))
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// FTS5 index over the searchable columns of `clipboard_entries`. It is an
+/// external-content table, so it stores only the index and reads text back
+/// from the entries; triggers keep the two in step. The tags cache changes
+/// through its own triggers, which fire the update trigger here in turn.
+async fn create_content_search_index(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_search USING fts5(
+            content, source_app, source_window, tags,
+            content = 'clipboard_entries',
+            content_rowid = 'id',
+            tokenize = 'unicode61 remove_diacritics 2'
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let index_new = "INSERT INTO clipboard_search (rowid, content, source_app, source_window, tags)
+                     VALUES (NEW.id, NEW.content, NEW.source_app, NEW.source_window, NEW.tags);";
+    let remove_old = "INSERT INTO clipboard_search (clipboard_search, rowid, content, source_app, source_window, tags)
+                      VALUES ('delete', OLD.id, OLD.content, OLD.source_app, OLD.source_window, OLD.tags);";
+
+    let triggers = [
+        ("clipboard_search_after_insert", "AFTER INSERT ON clipboard_entries", index_new.to_string()),
+        ("clipboard_search_after_delete", "AFTER DELETE ON clipboard_entries", remove_old.to_string()),
+        (
+            "clipboard_search_after_update",
+            "AFTER UPDATE OF content, source_app, source_window, tags ON clipboard_entries",
+            format!("{} {}", remove_old, index_new),
+        ),
+    ];
+    for (name, event, body) in triggers {
+        sqlx::query(&format!("CREATE TRIGGER IF NOT EXISTS {} {} BEGIN {} END", name, event, body))
+            .execute(pool)
+            .await?;
+    }
+
+    println!("🔎 Building the clipboard search index...");
+    sqlx::query("INSERT INTO clipboard_search (clipboard_search) VALUES ('rebuild')")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Indexes matching the two history orders, so paging from a cursor is a
+/// range scan. Cursors compare timestamps as text, so rows imported with
+/// another format (`2026-09-01T10:00:00Z`) are rewritten the way the app
+/// stores them first.
+async fn create_history_keyset_indexes(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let normalized = sqlx::query(
+        r#"
+        UPDATE clipboard_entries
+        SET timestamp = strftime('%Y-%m-%d %H:%M:%S', timestamp)
+        WHERE strftime('%Y-%m-%d %H:%M:%S', timestamp) IS NOT NULL
+          AND timestamp <> strftime('%Y-%m-%d %H:%M:%S', timestamp)
+        "#,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if normalized > 0 {
+        println!("🕒 Normalized the timestamp of {} entries", normalized);
+    }
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_history ON clipboard_entries(organization_id, timestamp DESC, id DESC)",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_history_pinned ON clipboard_entries(organization_id, is_pinned DESC, timestamp DESC, id DESC)",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let exists = sqlx::query("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")
+        .bind(table)
+        .bind(column)
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !exists {
+        println!("📝 Adding column {}.{}", table, column);
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
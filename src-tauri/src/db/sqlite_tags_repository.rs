@@ -236,22 +236,28 @@ impl SqliteTagRepository {
         Ok(count > 0)
     }
 
-    pub async fn get_tag_stats(
-        &self,
-        organization_id: &str,
-    ) -> Result<Vec<TagStats>, Error> {
-        let tags = self.get_organization_tags(organization_id).await?;
-
-        let stats = tags
-            .into_iter()
-            .map(|tag| TagStats {
-                tag_id: tag.id,
-                tag_name: tag.name,
-                usage_count: 0,
-                last_used_at: None,
-            })
-            .collect();
+    pub async fn get_tag_stats(&self, organization_id: &str) -> Result<Vec<TagStats>, Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.name, COUNT(et.entry_id) AS usage_count, MAX(et.created_at) AS last_used_at
+            FROM tags t
+            LEFT JOIN entry_tags et ON et.tag_id = t.id
+            WHERE t.organization_id = ?1
+            GROUP BY t.id, t.name
+            ORDER BY t.name ASC
+            "#
+        )
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await?;
 
+        let stats = rows.into_iter().map(|row| TagStats {
+            tag_id: row.get("id"),
+            tag_name: row.get("name"),
+            usage_count: row.get("usage_count"),
+            last_used_at: row.get("last_used_at"),
+        }).collect();
+        
         Ok(stats)
     }
 
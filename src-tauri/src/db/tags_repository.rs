@@ -68,11 +68,15 @@ impl TagRepository {
         }
     }
 
-    pub async fn create_tag(&self, new_tag: &NewTag) -> Result<Tag, Error> {
+    /// Creates the tag, or updates the color of the organization's tag with
+    /// the same name. Entries pushed from another device may already have
+    /// created it.
+    pub async fn upsert_by_name(&self, new_tag: &NewTag) -> Result<Tag, Error> {
         let row = sqlx::query(
             r#"
             INSERT INTO tags (organization_id, name, color, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5)
+            VALUES ($1, $2, $3, $4, $4)
+            ON CONFLICT (organization_id, LOWER(name)) DO UPDATE SET color = EXCLUDED.color
             RETURNING id, organization_id, name, color, created_at, updated_at
             "#
         )
@@ -80,7 +84,6 @@ impl TagRepository {
         .bind(&new_tag.name)
         .bind(&new_tag.color)
         .bind(Utc::now())
-        .bind(Utc::now())
         .fetch_one(&self.pool)
         .await?;
 
@@ -174,13 +177,25 @@ impl TagRepository {
     }
 
     pub async fn get_tag_stats(&self, organization_id: &str) -> Result<Vec<TagStats>, Error> {
-        let tags = self.get_organization_tags(organization_id).await?;
-        
-        let stats = tags.into_iter().map(|tag| TagStats {
-            tag_id: tag.id,
-            tag_name: tag.name,
-            usage_count: 0,
-            last_used_at: None,
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.name, COUNT(et.entry_id) AS usage_count, MAX(et.created_at) AS last_used_at
+            FROM tags t
+            LEFT JOIN entry_tags et ON et.tag_id = t.id
+            WHERE t.organization_id = $1
+            GROUP BY t.id, t.name
+            ORDER BY t.name ASC
+            "#
+        )
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let stats = rows.into_iter().map(|row| TagStats {
+            tag_id: row.get("id"),
+            tag_name: row.get("name"),
+            usage_count: row.get("usage_count"),
+            last_used_at: row.get("last_used_at"),
         }).collect();
         
         Ok(stats)
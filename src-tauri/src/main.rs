@@ -1,6 +1,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod db;
+mod capture;
+mod search;
 mod commands;
 mod command;
 mod auth;
@@ -17,7 +19,7 @@ use tauri::{WebviewWindowBuilder,  WindowEvent};
 use tauri_utils::config::WebviewUrl;
 use std::time::Duration;
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use tauri_plugin_autostart::MacosLauncher;
 use tauri_plugin_store::Builder as StoreBuilder;
 use winreg::enums::*;
@@ -31,10 +33,14 @@ use command::{
     get_recent_entries,
     get_entry_by_id,
     get_my_entries,
+    get_history_page,
+    get_history_summaries,
     delete_entry,
     update_entry,
     update_entry_content,
     search_entries,
+    fuzzy_search_entries,
+    get_entries_by_type,
 
     // Organization & tagging
     get_organization_tags,
@@ -45,6 +51,7 @@ use command::{
     get_tag_stats,
     assign_tag_to_entry,
     remove_tag_from_entry,
+    get_entries_by_tag,
 
     // Data management
     purge_unpinned_entries,
@@ -59,6 +66,14 @@ use command::{
     get_current_user_retain_tags,
     run_auto_purge_now,
 
+    // Capture settings
+    get_capture_settings,
+    update_capture_settings,
+    get_capture_rules,
+    create_capture_rule,
+    update_capture_rule,
+    delete_capture_rule,
+
     // User management
     login_user,
     logout_user,
@@ -107,12 +122,20 @@ use tauri::async_runtime::{self, Mutex};
 const POP_W: f64 = 460.0;
 const MIN_POP_H: f64 = 850.0;
 const MAX_POP_H: f64 = 900.0;
+pub const TRAY_ID: &str = "main";
+const TRAY_INCOGNITO_MINUTES: u64 = 15;
 
 // Application state
 #[derive(Debug)]
 pub struct AppState {
     pub is_database_ready: AtomicBool,
     pub is_clipboard_monitoring: AtomicBool,
+    pub capture_resume_at: AtomicU64, // Incognito end (unix seconds), 0 = no timer
+}
+
+/// Tray menu entries whose labels follow the capture state
+pub struct TrayCaptureMenu {
+    pub pause: MenuItem<tauri::Wry>,
 }
 
 #[derive(Debug)]
@@ -126,6 +149,7 @@ impl Default for AppState {
         Self {
             is_database_ready: AtomicBool::new(false),
             is_clipboard_monitoring: AtomicBool::new(false),
+            capture_resume_at: AtomicU64::new(0),
         }
     }
 }
@@ -182,12 +206,16 @@ fn main() {
             open_pricing_window,
             // Entry operations
             get_my_entries,
+            get_history_page,
+            get_history_summaries,
             get_recent_entries,
             get_entry_by_id,
             delete_entry,
             update_entry,
             update_entry_content,
             search_entries,
+            fuzzy_search_entries,
+            get_entries_by_type,
 
             // Tag operations
             get_organization_tags,
@@ -198,6 +226,7 @@ fn main() {
             get_tag_stats,
             assign_tag_to_entry,
             remove_tag_from_entry,
+            get_entries_by_tag,
 
             // Purge operations
             purge_entries_older_than,
@@ -212,6 +241,14 @@ fn main() {
             update_retain_tags_setting,
             run_auto_purge_now,
 
+            // Capture settings
+            get_capture_settings,
+            update_capture_settings,
+            get_capture_rules,
+            create_capture_rule,
+            update_capture_rule,
+            delete_capture_rule,
+
             // User authentication
             login_user,
             logout_user,
@@ -239,6 +276,16 @@ fn main() {
             resize_window,
 
             commands::editor::open_in_notepad_and_wait,
+            commands::clipboard::get_entry_thumbnail,
+            commands::clipboard::copy_entry_to_clipboard,
+            commands::clipboard::get_entry_content,
+            commands::clipboard::get_full_entry,
+            commands::clipboard::get_entry_files,
+            commands::clipboard::get_window_detection_method,
+            commands::clipboard::pause_clipboard_capture,
+            commands::clipboard::resume_clipboard_capture,
+            commands::clipboard::start_incognito_mode,
+            commands::clipboard::get_clipboard_capture_status,
 
             // Database status + sync
             check_database_status,
@@ -579,20 +626,39 @@ fn setup_tray_and_ui(app: &mut tauri::App) -> tauri::Result<()> {
     let app_handle = app.handle().clone();
 
     // Create tray menu
+    let pause = MenuItem::with_id(app, "pause_capture", "Pause capture", true, None::<&str>)?;
+    let incognito = MenuItem::with_id(
+        app,
+        "incognito",
+        format!("Incognito for {} minutes", TRAY_INCOGNITO_MINUTES),
+        true,
+        None::<&str>,
+    )?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&quit])?;
+    let menu = Menu::with_items(app, &[&pause, &incognito, &quit])?;
+    app.manage(TrayCaptureMenu { pause });
     let icon = app
         .default_window_icon()
         .expect("App should have a default icon")
         .clone();
 
     // Build tray icon
-    let tray = TrayIconBuilder::new()
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
         .tooltip("ClipTray — starting...")
         .menu(&menu)
         .show_menu_on_left_click(true)
         .on_menu_event(move |app, e| match e.id.as_ref() {
+            "pause_capture" => {
+                if capture::control::is_capture_enabled(app) {
+                    capture::control::pause(app, None);
+                } else {
+                    capture::control::resume(app);
+                }
+            }
+            "incognito" => {
+                capture::control::pause(app, Some(Duration::from_secs(TRAY_INCOGNITO_MINUTES * 60)));
+            }
             "quit" => app.exit(0),
             _ => {}
         })
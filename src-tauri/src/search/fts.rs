@@ -0,0 +1,46 @@
+// src/search/fts.rs
+//
+// Helpers around the `clipboard_search` FTS5 index (see the
+// `content_search_index` SQLite migration): quoting search terms for an FTS5
+// match expression, and turning FTS5 snippets into safe HTML.
+
+/// Marks the start and end of a match in snippets coming out of SQLite.
+/// Private-use characters can't clash with copied text the way `<mark>`
+/// could, and are swapped for tags after the snippet is escaped.
+pub const MATCH_START: &str = "\u{E000}";
+pub const MATCH_END: &str = "\u{E001}";
+
+/// Term matching `word` as a word or the start of one. Quoting makes FTS5
+/// take operators and punctuation in the input literally. `None` when the
+/// word has nothing searchable in it.
+pub fn word_term(word: &str) -> Option<String> {
+    word.chars()
+        .any(char::is_alphanumeric)
+        .then(|| format!("{}*", quote(word)))
+}
+
+/// Term matching the words of `phrase` next to each other, in order.
+pub fn phrase_term(phrase: &str) -> Option<String> {
+    phrase.chars().any(char::is_alphanumeric).then(|| quote(phrase))
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// HTML-escapes an FTS5 snippet and wraps its matches in `<mark>`.
+pub fn highlight_html(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len() + 16);
+    for c in snippet.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+
+    html.replace(MATCH_START, "<mark>").replace(MATCH_END, "</mark>")
+}
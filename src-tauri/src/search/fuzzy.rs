@@ -0,0 +1,542 @@
+// src/search/fuzzy.rs
+//
+// Typo-tolerant search for half-remembered snippets. Every word of the query
+// must match an entry, either as a subsequence ("cfgld" finds "config_load")
+// or within a few edits ("recieve" finds "receive"). Tight, word-aligned
+// matches score highest; ties keep the newer entry first.
+//
+// Matching runs over an in-memory index of the organization's most recent
+// entries. The capture pipeline records every saved copy, so the index is
+// warm by the time the user searches; it loads from SQLite on first use and
+// whenever the organization changes.
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db::schemas::ClipboardEntry;
+use crate::db::sqlite_database::SqliteClipboardRepository;
+
+/// Entries kept in the index, newest first.
+const INDEX_CAPACITY: usize = 2000;
+/// Only the start of long copies is searched.
+const MAX_INDEXED_CHARS: usize = 2048;
+/// Longest query word; the edit-distance matcher keeps one bit per char.
+const MAX_TERM_CHARS: usize = 63;
+
+const MATCH_SCORE: i32 = 16;
+const CONSECUTIVE_BONUS: i32 = 12;
+const WORD_START_BONUS: i32 = 8;
+const GAP_PENALTY: i32 = 3;
+const MAX_GAP_PENALTY: i32 = 15;
+const EDIT_PENALTY: i32 = 24;
+/// A subsequence spread over more than this many chars per term char is
+/// scattered letters, not a match.
+const MAX_SPREAD: usize = 4;
+/// Subsequence occurrences tried per term and entry.
+const MAX_WINDOWS: usize = 8;
+
+static INDEX: Lazy<RwLock<FuzzyIndex>> = Lazy::new(|| RwLock::new(FuzzyIndex::default()));
+
+#[derive(Default)]
+struct FuzzyIndex {
+    organization_id: Option<String>,
+    entries: VecDeque<IndexedEntry>,
+}
+
+struct IndexedEntry {
+    id: i64,
+    /// Lowercased start of the content, one char per content char
+    folded: String,
+}
+
+impl IndexedEntry {
+    fn new(entry: &ClipboardEntry) -> Self {
+        Self {
+            id: entry.id,
+            folded: entry.content.chars().take(MAX_INDEXED_CHARS).map(fold).collect(),
+        }
+    }
+}
+
+/// How well a query matched a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Char offsets of the matched characters, ascending
+    pub positions: Vec<usize>,
+}
+
+/// An entry found by fuzzy search.
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyHit {
+    #[serde(flatten)]
+    pub entry: ClipboardEntry,
+    pub score: i32,
+    /// Char offsets into `content` to highlight
+    pub positions: Vec<usize>,
+}
+
+/// Loads the organization's recent entries unless the index already holds them.
+pub async fn warm(pool: &SqlitePool, organization_id: &str) -> Result<(), String> {
+    let is_warm = INDEX
+        .read()
+        .map(|index| index.organization_id.as_deref() == Some(organization_id))
+        .unwrap_or(false);
+    if is_warm {
+        return Ok(());
+    }
+
+    let entries = SqliteClipboardRepository::get_by_organization(pool, organization_id, Some(INDEX_CAPACITY as i64))
+        .await
+        .map_err(|e| format!("Failed to load entries for fuzzy search: {}", e))?;
+
+    let mut index = INDEX.write().map_err(|_| "Fuzzy index lock poisoned".to_string())?;
+    index.entries = entries
+        .iter()
+        .filter(|entry| is_searchable(entry))
+        .map(IndexedEntry::new)
+        .collect();
+    index.organization_id = Some(organization_id.to_string());
+
+    println!("🔎 Fuzzy index warmed with {} entries", index.entries.len());
+    Ok(())
+}
+
+/// Adds a saved or edited entry to the index.
+pub async fn record(pool: &SqlitePool, entry: &ClipboardEntry) {
+    let Some(organization_id) = entry.organization_id.as_deref() else {
+        return;
+    };
+    if let Err(e) = warm(pool, organization_id).await {
+        eprintln!("⚠️ {}", e);
+        return;
+    }
+    if !is_searchable(entry) {
+        // E.g. edited into something that must not be searchable
+        forget(entry.id);
+        return;
+    }
+
+    if let Ok(mut index) = INDEX.write() {
+        match index.entries.iter_mut().find(|indexed| indexed.id == entry.id) {
+            Some(indexed) => *indexed = IndexedEntry::new(entry),
+            None => {
+                index.entries.push_front(IndexedEntry::new(entry));
+                index.entries.truncate(INDEX_CAPACITY);
+            }
+        }
+    }
+}
+
+/// Drops an entry that no longer exists.
+pub fn forget(entry_id: i64) {
+    if let Ok(mut index) = INDEX.write() {
+        index.entries.retain(|indexed| indexed.id != entry_id);
+    }
+}
+
+/// Ids of the best matching indexed entries, best first.
+pub fn search(organization_id: &str, query: &str, limit: usize) -> Vec<i64> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let Ok(index) = INDEX.read() else {
+        return Vec::new();
+    };
+    if index.organization_id.as_deref() != Some(organization_id) {
+        return Vec::new();
+    }
+
+    let mut scored: Vec<(i64, i32)> = index
+        .entries
+        .iter()
+        .filter_map(|indexed| {
+            let text: Vec<char> = indexed.folded.chars().collect();
+            match_terms(&terms, &text).map(|m| (indexed.id, m.score))
+        })
+        .collect();
+
+    // Stable: equal scores stay newest first
+    scored.sort_by_key(|&(_, score)| Reverse(score));
+    scored.into_iter().take(limit).map(|(id, _)| id).collect()
+}
+
+/// Matches the query against a text, for scoring and highlighting.
+pub fn match_text(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return None;
+    }
+
+    let text: Vec<char> = text.chars().take(MAX_INDEXED_CHARS).map(fold).collect();
+    match_terms(&terms, &text)
+}
+
+/// Images have no text to match, and sensitive copies stay out of search.
+pub fn is_searchable(entry: &ClipboardEntry) -> bool {
+    entry.content_type != "image" && !entry.is_sensitive
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn query_terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .map(|word| word.chars().take(MAX_TERM_CHARS).map(fold).collect())
+        .collect()
+}
+
+/// Every term has to match; scores add up and positions are merged.
+fn match_terms(terms: &[Vec<char>], text: &[char]) -> Option<FuzzyMatch> {
+    let mut score = 0;
+    let mut positions = Vec::new();
+
+    for term in terms {
+        let matched = [subsequence_match(term, text), approximate_match(term, text)]
+            .into_iter()
+            .flatten()
+            .max_by_key(|m| m.score)?;
+        score += matched.score;
+        positions.extend(matched.positions);
+    }
+
+    positions.sort_unstable();
+    positions.dedup();
+    Some(FuzzyMatch { score, positions })
+}
+
+/// The term's chars in order, in the best scoring of the first few tight
+/// windows that contain them.
+fn subsequence_match(term: &[char], text: &[char]) -> Option<FuzzyMatch> {
+    let mut best: Option<FuzzyMatch> = None;
+    let mut search_from = 0;
+
+    for _ in 0..MAX_WINDOWS {
+        let Some((start, end)) = subsequence_window(term, text, search_from) else {
+            break;
+        };
+        search_from = start + 1;
+
+        if end - start + 1 > term.len() * MAX_SPREAD {
+            continue;
+        }
+
+        let mut positions = Vec::with_capacity(term.len());
+        let mut next = 0;
+        for (i, c) in text.iter().enumerate().take(end + 1).skip(start) {
+            if next < term.len() && *c == term[next] {
+                positions.push(i);
+                next += 1;
+            }
+        }
+
+        let score = positions_score(&positions, text);
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(FuzzyMatch { score, positions });
+        }
+    }
+
+    best.filter(|m| m.score >= MATCH_SCORE / 2 * term.len() as i32)
+}
+
+/// First occurrence of the term as a subsequence at or after `from`,
+/// narrowed by walking back from where it ends.
+fn subsequence_window(term: &[char], text: &[char], from: usize) -> Option<(usize, usize)> {
+    let mut next = 0;
+    let mut end = None;
+    for (i, c) in text.iter().enumerate().skip(from) {
+        if *c == term[next] {
+            next += 1;
+            if next == term.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    let mut remaining = term.len();
+    for i in (from..=end).rev() {
+        if text[i] == term[remaining - 1] {
+            remaining -= 1;
+            if remaining == 0 {
+                return Some((i, end));
+            }
+        }
+    }
+
+    None
+}
+
+fn positions_score(positions: &[usize], text: &[char]) -> i32 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+
+    for &p in positions {
+        score += MATCH_SCORE;
+        if p == 0 || !text[p - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        match previous {
+            Some(prev) if p == prev + 1 => score += CONSECUTIVE_BONUS,
+            Some(prev) => {
+                let gap = (p - prev - 1) as i32;
+                score -= (GAP_PENALTY + gap - 1).min(MAX_GAP_PENALTY);
+            }
+            None => {}
+        }
+        previous = Some(p);
+    }
+
+    score
+}
+
+fn max_edits(term_len: usize) -> usize {
+    match term_len {
+        0..=3 => 0,
+        4..=6 => 1,
+        _ => 2,
+    }
+}
+
+/// The substring of the text closest to the term by edit distance, when
+/// it is within a few edits.
+fn approximate_match(term: &[char], text: &[char]) -> Option<FuzzyMatch> {
+    let allowed = max_edits(term.len());
+    if allowed == 0 {
+        return None;
+    }
+
+    let (end, distance) = best_approximate_end(term, text, allowed)?;
+    let window_start = (end + 1).saturating_sub(term.len() + allowed);
+    let positions = align(term, &text[window_start..=end])
+        .into_iter()
+        .map(|p| window_start + p)
+        .collect::<Vec<_>>();
+
+    let score = positions_score(&positions, text) - EDIT_PENALTY * distance as i32;
+    (score >= MATCH_SCORE / 2 * term.len() as i32).then_some(FuzzyMatch { score, positions })
+}
+
+/// End offset and edit distance of the best approximate occurrence of the
+/// term in the text (Myers' bit-parallel algorithm, free start).
+fn best_approximate_end(term: &[char], text: &[char], allowed: usize) -> Option<(usize, usize)> {
+    let m = term.len();
+    let mut peq: HashMap<char, u64> = HashMap::new();
+    for (i, c) in term.iter().enumerate() {
+        *peq.entry(*c).or_default() |= 1 << i;
+    }
+
+    let last = 1u64 << (m - 1);
+    let (mut pv, mut mv, mut distance) = (!0u64, 0u64, m);
+    let mut best: Option<(usize, usize)> = None;
+
+    for (j, c) in text.iter().enumerate() {
+        let eq = peq.get(c).copied().unwrap_or(0);
+        let xv = eq | mv;
+        let xh = (((eq & pv).wrapping_add(pv)) ^ pv) | eq;
+        let mut ph = mv | !(xh | pv);
+        let mut mh = pv & xh;
+
+        if ph & last != 0 {
+            distance += 1;
+        } else if mh & last != 0 {
+            distance -= 1;
+        }
+
+        ph <<= 1;
+        mh <<= 1;
+        pv = mh | !(xv | ph);
+        mv = ph & xv;
+
+        if distance <= allowed && best.is_none_or(|(_, d)| distance < d) {
+            best = Some((j, distance));
+            if distance == 0 {
+                break;
+            }
+        }
+    }
+
+    best
+}
+
+/// Offsets in `window` of the chars that line up exactly with the term in
+/// the cheapest alignment ending at the window's last char.
+fn align(term: &[char], window: &[char]) -> Vec<usize> {
+    let (m, n) = (term.len(), window.len());
+    let mut cost = vec![vec![0usize; n + 1]; m + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=m {
+        for j in 1..=n {
+            let substitution = cost[i - 1][j - 1] + usize::from(term[i - 1] != window[j - 1]);
+            cost[i][j] = substitution.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    let mut positions = Vec::new();
+    let (mut i, mut j) = (m, n);
+    while i > 0 {
+        if j > 0 && term[i - 1] == window[j - 1] && cost[i][j] == cost[i - 1][j - 1] {
+            positions.push(j - 1);
+            i -= 1;
+            j -= 1;
+        } else if j > 0 && cost[i][j] == cost[i - 1][j - 1] + 1 {
+            i -= 1;
+            j -= 1;
+        } else if cost[i][j] == cost[i - 1][j] + 1 {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+
+    positions.reverse();
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    /// Textbook DP for the same question: the first end offset with the
+    /// fewest edits, start anywhere in the text.
+    fn reference_end(term: &[char], text: &[char], allowed: usize) -> Option<(usize, usize)> {
+        let mut previous: Vec<usize> = (0..=term.len()).collect();
+        let mut best: Option<(usize, usize)> = None;
+
+        for (j, c) in text.iter().enumerate() {
+            let mut current = vec![0; term.len() + 1];
+            for i in 1..=term.len() {
+                let substitution = previous[i - 1] + usize::from(term[i - 1] != *c);
+                current[i] = substitution.min(previous[i] + 1).min(current[i - 1] + 1);
+            }
+            let distance = current[term.len()];
+            if distance <= allowed && best.is_none_or(|(_, d)| distance < d) {
+                best = Some((j, distance));
+            }
+            previous = current;
+        }
+
+        best
+    }
+
+    fn myers(term: &str, text: &str, allowed: usize) -> Option<(usize, usize)> {
+        best_approximate_end(&chars(term), &chars(text), allowed)
+    }
+
+    #[test]
+    fn myers_finds_exact_occurrences() {
+        assert_eq!(myers("load", "config_load()", 1), Some((10, 0)));
+        assert_eq!(myers("a", "bab", 0), Some((1, 0)));
+    }
+
+    #[test]
+    fn myers_counts_each_kind_of_edit() {
+        // Substitution, insertion, deletion, transposition (two edits)
+        assert_eq!(myers("receive", "we recieve mail", 2).map(|(_, d)| d), Some(2));
+        assert_eq!(myers("colour", "the color red", 1).map(|(_, d)| d), Some(1));
+        assert_eq!(myers("color", "the colour red", 1).map(|(_, d)| d), Some(1));
+        assert_eq!(myers("kitten", "sitten", 1), Some((5, 1)));
+    }
+
+    #[test]
+    fn myers_respects_the_edit_budget() {
+        assert_eq!(myers("receive", "we recieve mail", 1), None);
+        assert_eq!(myers("deploy", "nothing alike", 2), None);
+        assert_eq!(myers("abc", "", 3), None);
+    }
+
+    #[test]
+    fn myers_handles_the_longest_terms() {
+        let term = "x".repeat(MAX_TERM_CHARS);
+        let text = format!("ab{}cd", "x".repeat(MAX_TERM_CHARS - 1));
+        assert_eq!(myers(&term, &text, 2), Some((MAX_TERM_CHARS, 1)));
+    }
+
+    #[test]
+    fn myers_agrees_with_the_dp_reference() {
+        // Small alphabet so near matches are common
+        let alphabet = ['a', 'b', 'c', ' '];
+        let mut seed = 0x2545_f491_u32;
+        let mut next = |len: usize| -> Vec<char> {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    alphabet[(seed >> 16) as usize % alphabet.len()]
+                })
+                .collect()
+        };
+
+        for round in 0..500 {
+            let term = next(1 + round % 9);
+            let text = next(round % 40);
+            for allowed in 0..=2 {
+                assert_eq!(
+                    best_approximate_end(&term, &text, allowed),
+                    reference_end(&term, &text, allowed),
+                    "term {:?} text {:?} allowed {}",
+                    term.iter().collect::<String>(),
+                    text.iter().collect::<String>(),
+                    allowed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn typos_and_abbreviations_match() {
+        let typo = match_text("recieve", "Please receive the package").unwrap();
+        assert!(typo.positions.iter().all(|p| (7..14).contains(p)));
+
+        let abbreviation = match_text("cfgld", "config_load").unwrap();
+        assert_eq!(abbreviation.positions, vec![0, 3, 5, 7, 10]);
+
+        assert!(match_text("cfgld", "completely unrelated").is_none());
+        assert!(match_text("   ", "anything").is_none());
+    }
+
+    #[test]
+    fn every_term_has_to_match() {
+        assert!(match_text("config load", "config_load").is_some());
+        assert!(match_text("config deploy", "config_load").is_none());
+    }
+
+    #[test]
+    fn tighter_matches_score_higher() {
+        let tight = match_text("load", "load the config").unwrap();
+        let spread = match_text("load", "lots of airy dust").unwrap();
+        assert!(tight.score > spread.score);
+    }
+
+    #[tokio::test]
+    async fn sensitive_entries_are_not_searchable() {
+        let pool = crate::db::sqlite_database::memory_pool().await;
+        let save = |content: &str, sensitive: bool| {
+            let mut entry = crate::db::schemas::NewClipboardEntry::from_monitoring_data(
+                content.to_string(),
+                "editor".to_string(),
+                "notes.txt".to_string(),
+            );
+            entry.organization_id = Some("org-a".to_string());
+            entry.is_sensitive = sensitive;
+            SqliteClipboardRepository::save_entry(&pool, entry)
+        };
+
+        assert!(is_searchable(&save("meeting notes", false).await.unwrap()));
+        assert!(!is_searchable(&save("hunter2", true).await.unwrap()));
+    }
+}
@@ -0,0 +1,4 @@
+// src/search/mod.rs
+pub mod fts;
+pub mod fuzzy;
+pub mod query;
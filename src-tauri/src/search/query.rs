@@ -0,0 +1,367 @@
+// src/search/query.rs
+//
+// The search box syntax. A query is a list of terms that must all match:
+//
+//   tag:work app:firefox type:url pinned:true after:2026-09-01 "exact phrase" words
+//
+// Bare words match the start of any word in the content, source app, window
+// or tags; quoted phrases match those words in that order. Operator values
+// can be quoted too (`tag:"client work"`). A leading `-` excludes entries
+// matching a word, phrase or operator (`-draft`, `-"lorem ipsum"`,
+// `-tag:archive`). Any other `name:` is an unknown operator, so typos like
+// `tga:work` are reported instead of searched; URLs (`https://…`), drive
+// letters (`C:\dir`) and times (`12:30`) are words. Quote text that has to
+// contain a colon (`"Error: timeout"`).
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::capture::classifier;
+use crate::search::fts;
+
+const OPERATORS: [&str; 6] = ["tag", "app", "type", "pinned", "after", "before"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Full-text word, matched as a prefix
+    Word(String),
+    /// Full-text phrase, matched exactly
+    Phrase(String),
+    /// Linked to a tag with this name (case-insensitive)
+    Tag(String),
+    /// Copied from an app whose name contains this (case-insensitive)
+    App(String),
+    /// Primary or secondary classifier type
+    Type(String),
+    Pinned(bool),
+    /// Copied on this day or later (local time)
+    After(NaiveDate),
+    /// Copied before this day (local time)
+    Before(NaiveDate),
+    /// Entries the inner filter does not match
+    Not(Box<Filter>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    UnknownOperator { name: String, column: usize },
+    MissingValue { operator: &'static str, example: &'static str },
+    InvalidValue { operator: &'static str, value: String, expected: String },
+    UnterminatedQuote { column: usize },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::UnknownOperator { name, column } => write!(
+                f,
+                "Unknown search operator '{}:' at column {}. Supported operators: {}. \
+                 Put text with a colon in quotes to search for it",
+                name,
+                column,
+                OPERATORS.map(|operator| format!("{}:", operator)).join(", ")
+            ),
+            QueryError::MissingValue { operator, example } => {
+                write!(f, "'{}:' needs a value, e.g. {}", operator, example)
+            }
+            QueryError::InvalidValue { operator, value, expected } => {
+                write!(f, "Invalid value '{}' for '{}:', expected {}", value, operator, expected)
+            }
+            QueryError::UnterminatedQuote { column } => {
+                write!(f, "The quote opened at column {} is never closed", column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Parses the search box text into filters.
+pub fn parse(input: &str) -> Result<SearchQuery, QueryError> {
+    let column = |rest: &str| input[..input.len() - rest.len()].chars().count() + 1;
+
+    let mut filters = Vec::new();
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        let token_column = column(rest);
+
+        // `-` in front of anything but whitespace or another `-` negates it
+        let negated = rest.len() > 1
+            && rest.starts_with('-')
+            && !rest[1..].starts_with(|c: char| c.is_whitespace() || c == '-');
+        if negated {
+            rest = &rest[1..];
+        }
+
+        let filter = if let Some(quoted) = rest.strip_prefix('"') {
+            let (phrase, after) =
+                take_quoted(quoted).ok_or(QueryError::UnterminatedQuote { column: column(rest) })?;
+            rest = after;
+            (!phrase.trim().is_empty()).then(|| Filter::Phrase(phrase.trim().to_string()))
+        } else {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..word_end];
+
+            match operator_name(word, token_column)? {
+                Some(operator) => {
+                    let value_start = &rest[operator.len() + 1..];
+                    let (value, after) = match value_start.strip_prefix('"') {
+                        Some(quoted) => take_quoted(quoted)
+                            .ok_or(QueryError::UnterminatedQuote { column: column(value_start) })?,
+                        None => {
+                            let end = value_start.find(char::is_whitespace).unwrap_or(value_start.len());
+                            (&value_start[..end], &value_start[end..])
+                        }
+                    };
+                    rest = after;
+                    Some(operator_filter(operator, value.trim())?)
+                }
+                None => {
+                    rest = &rest[word_end..];
+                    Some(Filter::Word(word.to_string()))
+                }
+            }
+        };
+
+        if let Some(filter) = filter {
+            filters.push(if negated { Filter::Not(Box::new(filter)) } else { filter });
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(SearchQuery { filters })
+}
+
+impl SearchQuery {
+    /// FTS5 match expression for the words and phrases, `None` when the
+    /// query only has operators. Negated ones are left to the SQL filters.
+    pub fn match_expression(&self) -> Option<String> {
+        let terms: Vec<String> = self
+            .filters
+            .iter()
+            .filter_map(|filter| match filter {
+                Filter::Word(word) => fts::word_term(word),
+                Filter::Phrase(phrase) => fts::phrase_term(phrase),
+                _ => None,
+            })
+            .collect();
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(terms.join(" "))
+        }
+    }
+}
+
+/// Start of a local calendar day, for the date operators.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// The operator `word` starts with (any case), `None` for a plain word.
+/// Other letters before a colon are an unknown operator, except in URLs and
+/// drive letters (`C:\Users`); times like `12:30` aren't letters.
+fn operator_name(word: &str, column: usize) -> Result<Option<&'static str>, QueryError> {
+    let Some((name, value)) = word.split_once(':') else {
+        return Ok(None);
+    };
+
+    if let Some(operator) = OPERATORS.into_iter().find(|operator| operator.eq_ignore_ascii_case(name)) {
+        return Ok(Some(operator));
+    }
+
+    let looks_like_operator = name.len() > 1
+        && name.chars().all(|c| c.is_ascii_alphabetic())
+        && !value.starts_with("//");
+    if looks_like_operator {
+        return Err(QueryError::UnknownOperator { name: name.to_string(), column });
+    }
+
+    Ok(None)
+}
+
+/// Splits `text` after an opening quote into the quoted part and the rest.
+fn take_quoted(text: &str) -> Option<(&str, &str)> {
+    let end = text.find('"')?;
+    Some((&text[..end], &text[end + 1..]))
+}
+
+fn operator_filter(operator: &'static str, value: &str) -> Result<Filter, QueryError> {
+    if value.is_empty() {
+        let example = match operator {
+            "tag" => "tag:work",
+            "app" => "app:firefox",
+            "type" => "type:url",
+            "pinned" => "pinned:true",
+            "after" => "after:2026-09-01",
+            _ => "before:2026-09-01",
+        };
+        return Err(QueryError::MissingValue { operator, example });
+    }
+
+    let invalid = |expected: String| QueryError::InvalidValue {
+        operator,
+        value: value.to_string(),
+        expected,
+    };
+
+    match operator {
+        "tag" => Ok(Filter::Tag(value.to_string())),
+        "app" => Ok(Filter::App(value.to_string())),
+        "type" => {
+            let content_type = value.to_lowercase();
+            if classifier::known_types().any(|known| known == content_type) {
+                Ok(Filter::Type(content_type))
+            } else {
+                Err(invalid(format!(
+                    "one of {}",
+                    classifier::known_types().collect::<Vec<_>>().join(", ")
+                )))
+            }
+        }
+        "pinned" => match value.to_lowercase().as_str() {
+            "true" | "yes" => Ok(Filter::Pinned(true)),
+            "false" | "no" => Ok(Filter::Pinned(false)),
+            _ => Err(invalid("true or false".to_string())),
+        },
+        _ => {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| invalid("a date like 2026-09-01".to_string()))?;
+            Ok(if operator == "after" { Filter::After(date) } else { Filter::Before(date) })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(input: &str) -> Vec<Filter> {
+        parse(input).unwrap().filters
+    }
+
+    fn word(text: &str) -> Filter {
+        Filter::Word(text.to_string())
+    }
+
+    fn not(filter: Filter) -> Filter {
+        Filter::Not(Box::new(filter))
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_each_operator() {
+        assert_eq!(filters("tag:work"), vec![Filter::Tag("work".to_string())]);
+        assert_eq!(filters("app:firefox"), vec![Filter::App("firefox".to_string())]);
+        assert_eq!(filters("type:URL"), vec![Filter::Type("url".to_string())]);
+        assert_eq!(filters("pinned:yes pinned:false"), vec![Filter::Pinned(true), Filter::Pinned(false)]);
+        assert_eq!(filters("after:2026-09-01"), vec![Filter::After(date("2026-09-01"))]);
+        assert_eq!(filters("before:2026-09-30"), vec![Filter::Before(date("2026-09-30"))]);
+        assert_eq!(filters("TAG:Work"), vec![Filter::Tag("Work".to_string())]);
+    }
+
+    #[test]
+    fn combines_operators_and_words() {
+        assert_eq!(
+            filters("  tag:work   deploy app:term  "),
+            vec![Filter::Tag("work".to_string()), word("deploy"), Filter::App("term".to_string())]
+        );
+    }
+
+    #[test]
+    fn unknown_operators_are_errors() {
+        assert_eq!(
+            parse("tag:work tga:home"),
+            Err(QueryError::UnknownOperator { name: "tga".to_string(), column: 10 })
+        );
+        assert_eq!(
+            parse("pined:true"),
+            Err(QueryError::UnknownOperator { name: "pined".to_string(), column: 1 })
+        );
+        assert_eq!(
+            parse("x -note:foo"),
+            Err(QueryError::UnknownOperator { name: "note".to_string(), column: 3 })
+        );
+        assert!(matches!(parse("Error: timeout"), Err(QueryError::UnknownOperator { .. })));
+
+        let message = parse("tga:home").unwrap_err().to_string();
+        assert!(message.contains("'tga:' at column 1"));
+        assert!(message.contains("tag:, app:, type:, pinned:, after:, before:"));
+    }
+
+    #[test]
+    fn urls_drive_letters_and_times_are_words() {
+        assert_eq!(filters("https://example.com/a"), vec![word("https://example.com/a")]);
+        assert_eq!(filters("ftp://host"), vec![word("ftp://host")]);
+        assert_eq!(filters(r"C:\Users d:"), vec![word(r"C:\Users"), word("d:")]);
+        assert_eq!(filters("10:30 12:30:59"), vec![word("10:30"), word("12:30:59")]);
+        // Quoted text is never an operator
+        assert_eq!(filters(r#""Error: timeout""#), vec![Filter::Phrase("Error: timeout".to_string())]);
+    }
+
+    #[test]
+    fn quoted_phrases_and_values() {
+        assert_eq!(
+            filters(r#""exact phrase" tag:"client work""#),
+            vec![Filter::Phrase("exact phrase".to_string()), Filter::Tag("client work".to_string())]
+        );
+        // Empty quotes add nothing
+        assert_eq!(filters(r#""  " word"#), vec![word("word")]);
+    }
+
+    #[test]
+    fn unterminated_quotes_report_their_column() {
+        assert_eq!(parse(r#"foo "bar"#), Err(QueryError::UnterminatedQuote { column: 5 }));
+        assert_eq!(parse(r#"tag:"client"#), Err(QueryError::UnterminatedQuote { column: 5 }));
+    }
+
+    #[test]
+    fn negates_words_phrases_and_operators() {
+        assert_eq!(
+            filters(r#"report -draft -"lorem ipsum" -tag:archive"#),
+            vec![
+                word("report"),
+                not(word("draft")),
+                not(Filter::Phrase("lorem ipsum".to_string())),
+                not(Filter::Tag("archive".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn dashes_that_are_not_negation() {
+        assert_eq!(filters("- --verbose a-b"), vec![word("-"), word("--verbose"), word("a-b")]);
+    }
+
+    #[test]
+    fn match_expression_leaves_out_negated_text() {
+        let query = parse(r#"report "exact phrase" -draft tag:work"#).unwrap();
+        assert_eq!(query.match_expression().as_deref(), Some(r#""report"* "exact phrase""#));
+
+        assert_eq!(parse("-draft tag:work").unwrap().match_expression(), None);
+    }
+
+    #[test]
+    fn rejects_bad_operator_values() {
+        assert!(matches!(parse("tag:"), Err(QueryError::MissingValue { operator: "tag", .. })));
+        assert!(matches!(parse("-app: x"), Err(QueryError::MissingValue { operator: "app", .. })));
+        assert!(matches!(parse("pinned:maybe"), Err(QueryError::InvalidValue { operator: "pinned", .. })));
+        assert!(matches!(parse("after:yesterday"), Err(QueryError::InvalidValue { operator: "after", .. })));
+        assert!(matches!(parse("type:spreadsheet"), Err(QueryError::InvalidValue { operator: "type", .. })));
+    }
+}